@@ -0,0 +1,115 @@
+//! Renders growing trees and reports the time spent per node and per output byte.
+//!
+//! Both figures should stay roughly flat as the trees double in size.
+
+use std::{
+    fmt::{self, Write},
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use txtree::prelude::*;
+
+type Shape = (&'static str, fn(usize) -> Node, &'static [usize]);
+
+struct Count(usize);
+impl Write for Count {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 += s.len();
+        Ok(())
+    }
+}
+
+fn wide(size: usize) -> Node {
+    let children = (0..size)
+        .map(|i| Node::new(format!("leaf{}", i), vec![]))
+        .collect::<Vec<_>>();
+    Node::new(String::from("root"), children)
+}
+
+fn deep(size: usize) -> Node {
+    (0..size).fold(Node::new(String::from("leaf"), vec![]), |node, i| {
+        Node::new(format!("node{}", i), vec![node])
+    })
+}
+
+fn balanced(size: usize) -> Node {
+    fn build(size: usize, name: usize) -> Node {
+        let rest = size.saturating_sub(1);
+        let children = match rest {
+            0 => vec![],
+            1 => vec![build(1, name * 2)],
+            _ => vec![
+                build(rest / 2, name * 2),
+                build(rest - rest / 2, name * 2 + 1),
+            ],
+        };
+        Node::new(format!("n{}", name), children)
+    }
+    build(size, 1)
+}
+
+fn modes() -> Vec<(&'static str, RenderMode)> {
+    let mode = |chain, vertical, invert, centered| {
+        RenderMode::new(
+            SymbolsBuilder::new().build(),
+            0,
+            0,
+            chain,
+            vertical,
+            invert,
+            AlignmentBuilder::new().centered(centered).build(),
+        )
+    };
+    vec![
+        ("default", mode(false, false, false, false)),
+        ("chain", mode(true, false, false, false)),
+        ("middle", mode(false, false, false, true)),
+        ("invert", mode(false, false, true, false)),
+        ("vertical", mode(false, true, false, false)),
+    ]
+}
+
+fn measure(mode: &RenderMode, node: &Node) -> (Duration, usize) {
+    let mut best = Duration::MAX;
+    let mut bytes = 0;
+    for _ in 0..5 {
+        let mut count = Count(0);
+        let start = Instant::now();
+        mode.render(black_box(node), &mut count).unwrap();
+        best = best.min(start.elapsed());
+        bytes = count.0;
+    }
+    (best, bytes)
+}
+
+fn main() {
+    let shapes: [Shape; 3] = [
+        ("wide", wide, &[25_000, 50_000, 100_000, 200_000]),
+        ("balanced", balanced, &[25_000, 50_000, 100_000, 200_000]),
+        ("deep", deep, &[500, 1_000, 2_000, 4_000]),
+    ];
+
+    println!(
+        "{:<10} {:<10} {:>8} {:>12} {:>10} {:>10}",
+        "shape", "mode", "nodes", "bytes", "ns/node", "ns/byte"
+    );
+    for (shape, build, sizes) in shapes {
+        for (name, mode) in modes() {
+            for &size in sizes {
+                let node = build(size);
+                let (time, bytes) = measure(&mode, &node);
+                let nanos = time.as_nanos() as f64;
+                println!(
+                    "{:<10} {:<10} {:>8} {:>12} {:>10.1} {:>10.2}",
+                    shape,
+                    name,
+                    size,
+                    bytes,
+                    nanos / size as f64,
+                    nanos / bytes as f64,
+                );
+            }
+        }
+    }
+}
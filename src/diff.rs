@@ -0,0 +1,264 @@
+//! Structural comparison of trees.
+
+use std::collections::{HashMap, HashSet};
+
+use crate::Node;
+
+const ADDED: &str = "+ ";
+const REMOVED: &str = "- ";
+const MOVED: &str = "~ ";
+
+/// Compares two lists of trees, returning a single list showing both.
+///
+/// Nodes are matched by name among their siblings. Names of nodes only in
+/// `new` start with `+ `, names of nodes only in `old` start with `- `.
+/// A subtree removed in one place and added under the same name in another
+/// is shown once where it was added, starting with `~ ` and ending with the
+/// path it was moved from. Children follow the order of `new`, with removed
+/// ones kept after the sibling they followed.
+pub fn diff(old: &[Node], new: &[Node]) -> Vec<Node> {
+    let mut entries = compare(old, new, &mut Vec::new());
+
+    let mut removed = HashMap::<&str, Vec<Vec<usize>>>::new();
+    collect(&entries, &mut Vec::new(), &mut |entry, at| {
+        if let (Change::Removed, Some(old)) = (&entry.change, entry.old) {
+            removed
+                .entry(old.name.as_str())
+                .or_default()
+                .push(at.to_vec());
+        }
+        true
+    });
+    let mut moves = Vec::new();
+    // Removed entries taken by a move, and the ones containing them
+    let (mut taken, mut above) = (HashSet::new(), HashSet::new());
+    collect(&entries, &mut Vec::new(), &mut |entry, at| {
+        let Some(new) = entry.new.filter(|_| matches!(entry.change, Change::Added)) else {
+            return true;
+        };
+        let Some(candidates) = removed.get_mut(new.name.as_str()) else {
+            return true;
+        };
+        let free = candidates.iter().rposition(|from| {
+            !above.contains(from) && (1..=from.len()).all(|end| !taken.contains(&from[..end]))
+        });
+        let Some(index) = free else {
+            return true;
+        };
+        let from = candidates.remove(index);
+        above.extend((1..from.len()).map(|end| from[..end].to_vec()));
+        taken.insert(from.clone());
+        moves.push((from, at.to_vec()));
+        // Nodes within a moved subtree are compared with it instead
+        false
+    });
+    for (from, to) in moves {
+        let source = entry_mut(&mut entries, &from);
+        let (old, path) = (source.old, source.path.clone());
+        source.change = Change::Gone;
+        let target = entry_mut(&mut entries, &to);
+        target.change = Change::Moved(path);
+        target.children = compare(
+            old.map(Node::children).unwrap_or_default(),
+            target.new.map(Node::children).unwrap_or_default(),
+            &mut Vec::new(),
+        );
+    }
+
+    entries.into_iter().filter_map(Entry::into_node).collect()
+}
+
+#[derive(Clone)]
+enum Change {
+    Unchanged,
+    Added,
+    Removed,
+    /// Added here, with the path of the parent it was removed from
+    Moved(String),
+    /// Removed, but shown where it was moved to
+    Gone,
+}
+
+struct Entry<'a> {
+    change: Change,
+    old: Option<&'a Node>,
+    new: Option<&'a Node>,
+    /// Names of the ancestors in the old trees, joined like a path
+    path: String,
+    children: Vec<Entry<'a>>,
+}
+impl Entry<'_> {
+    fn into_node(self) -> Option<Node> {
+        let name = match &self.change {
+            Change::Gone => return None,
+            Change::Added => format!("{}{}", ADDED, self.new?.name),
+            Change::Removed => format!("{}{}", REMOVED, self.old?.name),
+            Change::Unchanged => self.new?.name.clone(),
+            Change::Moved(from) => format!("{}{} (from {})", MOVED, self.new?.name, from),
+        };
+        Some(Node::new(
+            name,
+            self.children
+                .into_iter()
+                .filter_map(Entry::into_node)
+                .collect(),
+        ))
+    }
+}
+
+fn compare<'a>(old: &'a [Node], new: &'a [Node], path: &mut Vec<&'a str>) -> Vec<Entry<'a>> {
+    let mut unclaimed = HashMap::<&str, Vec<usize>>::new();
+    for (index, node) in old.iter().enumerate().rev() {
+        unclaimed.entry(node.name.as_str()).or_default().push(index);
+    }
+    let claims = new
+        .iter()
+        .map(|node| unclaimed.get_mut(node.name.as_str()).and_then(Vec::pop))
+        .collect::<Vec<_>>();
+    let mut claimed = vec![false; old.len()];
+    claims
+        .iter()
+        .flatten()
+        .for_each(|&index| claimed[index] = true);
+
+    let joined = format!("/{}", path.join("/"));
+    let mut entries = Vec::with_capacity(new.len());
+    let mut next = 0;
+    for (node, claim) in new.iter().zip(claims) {
+        let Some(index) = claim else {
+            entries.push(one_sided(node, Change::Added, path));
+            continue;
+        };
+        entries.extend(
+            (next..index)
+                .filter(|&i| !claimed[i])
+                .map(|i| one_sided(&old[i], Change::Removed, path)),
+        );
+        next = next.max(index + 1);
+
+        path.push(&old[index].name);
+        let children = compare(&old[index].children, &node.children, path);
+        path.pop();
+        entries.push(Entry {
+            change: Change::Unchanged,
+            old: Some(&old[index]),
+            new: Some(node),
+            path: joined.clone(),
+            children,
+        });
+    }
+    entries.extend(
+        (next..old.len())
+            .filter(|&i| !claimed[i])
+            .map(|i| one_sided(&old[i], Change::Removed, path)),
+    );
+    entries
+}
+
+/// Entry for a node only `Added` or `Removed`, with entries for its
+/// descendants so they can be matched as moves too.
+fn one_sided<'a>(node: &'a Node, change: Change, path: &mut Vec<&'a str>) -> Entry<'a> {
+    let joined = format!("/{}", path.join("/"));
+    path.push(&node.name);
+    let children = node
+        .children
+        .iter()
+        .map(|child| one_sided(child, change.clone(), path))
+        .collect();
+    path.pop();
+    let (old, new) = match change {
+        Change::Added => (None, Some(node)),
+        _ => (Some(node), None),
+    };
+    Entry {
+        change,
+        old,
+        new,
+        path: joined,
+        children,
+    }
+}
+
+/// Calls `visit` on every entry, along with the indices leading to it.
+/// Entries below one are skipped when `visit` returns `false` for it.
+fn collect<'a>(
+    entries: &[Entry<'a>],
+    at: &mut Vec<usize>,
+    visit: &mut impl FnMut(&Entry<'a>, &[usize]) -> bool,
+) {
+    for (index, entry) in entries.iter().enumerate() {
+        at.push(index);
+        if visit(entry, at) {
+            collect(&entry.children, at, visit);
+        }
+        at.pop();
+    }
+}
+
+fn entry_mut<'e, 'a>(entries: &'e mut [Entry<'a>], at: &[usize]) -> &'e mut Entry<'a> {
+    let (first, rest) = at.split_first().expect("Path should not be empty");
+    match rest {
+        [] => &mut entries[*first],
+        _ => entry_mut(&mut entries[*first].children, rest),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{format::Bracket, parser::parse_complete, parser::ParseMode};
+
+    fn diffed(old: &str, new: &str) -> String {
+        let mode = ParseMode::default();
+        let old = parse_complete(old, mode.clone()).unwrap();
+        let new = parse_complete(new, mode.clone()).unwrap();
+        Bracket::new(&diff(&old, &new), &mode).to_string()
+    }
+
+    #[test]
+    fn unchanged() {
+        assert_eq!(diffed("a[b,c[d]]", "a[b,c[d]]"), "a[b,c[d]]");
+    }
+
+    #[test]
+    fn added() {
+        assert_eq!(diffed("a[b]", "a[b,c[d]]"), "a[b,+ c[+ d]]");
+    }
+
+    #[test]
+    fn removed() {
+        assert_eq!(diffed("a[b,c[d],e]", "a[b,e]"), "a[b,- c[- d],e]");
+    }
+
+    #[test]
+    fn moved() {
+        assert_eq!(diffed("a[b[c],d]", "a[b,d[c]]"), "a[b,d[~ c (from /a/b)]]");
+    }
+
+    #[test]
+    fn moved_subtree_compares_children() {
+        assert_eq!(
+            diffed("a[b[c[x,y]],d]", "a[b,d[c[y,z]]]"),
+            "a[b,d[~ c (from /a/b)[- x,y,+ z]]]"
+        );
+    }
+
+    #[test]
+    fn moved_into_added_parent() {
+        assert_eq!(diffed("a[d[e]]", "a[d,f[e]]"), "a[d,+ f[~ e (from /a/d)]]");
+    }
+
+    #[test]
+    fn moved_out_of_removed_parent() {
+        assert_eq!(diffed("a[x[e],y]", "a[y[e]]"), "a[- x,y[~ e (from /a/x)]]");
+    }
+
+    #[test]
+    fn duplicate_siblings() {
+        assert_eq!(diffed("a[b,b,c]", "a[b,c,c]"), "a[b,- b,c,+ c]");
+        assert_eq!(
+            diffed("a[b[x],b[y]]", "a[b[y],b[x]]"),
+            "a[b[~ y (from /a/b)],b[~ x (from /a/b)]]"
+        );
+    }
+}
@@ -0,0 +1,34 @@
+mod bracket;
+mod dot;
+mod html;
+mod json;
+mod latex;
+mod mermaid;
+mod plantuml;
+
+use std::fmt::{self, Write};
+
+pub use self::{
+    bracket::Bracket,
+    dot::{Dot, RankDir},
+    html::Html,
+    json::Json,
+    latex::{Latex, LatexDialect},
+    mermaid::Mermaid,
+    plantuml::PlantUml,
+};
+
+/// Writes `text` with the characters that are special in xml and html replaced.
+pub(crate) fn escape_xml<W: Write>(out: &mut W, text: &str) -> fmt::Result {
+    for c in text.chars() {
+        match c {
+            '&' => out.write_str("&amp;")?,
+            '<' => out.write_str("&lt;")?,
+            '>' => out.write_str("&gt;")?,
+            '"' => out.write_str("&quot;")?,
+            '\'' => out.write_str("&apos;")?,
+            c => out.write_char(c)?,
+        }
+    }
+    Ok(())
+}
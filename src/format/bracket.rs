@@ -0,0 +1,167 @@
+use std::fmt::{self, Display, Write};
+
+use crate::{
+    parser::{ParseMode, ESCAPE, NEWLINE},
+    Node,
+};
+
+/// Writes nodes in the syntax read by [`ParseMode`].
+///
+/// Parts of names that would be read as delimiters or brackets are escaped,
+/// including the end of a name that would run into the delimiter or bracket
+/// written after it, so parsing the output with the same mode gives back the
+/// same nodes.
+#[derive(Debug, Clone, Copy)]
+pub struct Bracket<'a> {
+    nodes: &'a [Node],
+    mode: &'a ParseMode,
+}
+impl<'a> Bracket<'a> {
+    pub fn new(nodes: &'a [Node], mode: &'a ParseMode) -> Self {
+        Bracket { nodes, mode }
+    }
+
+    fn list(&self, f: &mut fmt::Formatter<'_>, nodes: &[Node]) -> fmt::Result {
+        for (index, node) in nodes.iter().enumerate() {
+            if index > 0 {
+                f.write_str(&self.mode.delimiter)?;
+            }
+            self.name(f, &node.name)?;
+            if !node.children.is_empty() {
+                f.write_str(&self.mode.brackets.open)?;
+                self.list(f, &node.children)?;
+                f.write_str(&self.mode.brackets.close)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn name(&self, f: &mut fmt::Formatter<'_>, name: &str) -> fmt::Result {
+        let first = name.find(|c: char| !c.is_whitespace());
+        let last = name.rfind(|c: char| !c.is_whitespace());
+        for (pos, c) in name.char_indices() {
+            if c == '\n' {
+                f.write_char(ESCAPE)?;
+                f.write_char(NEWLINE)?;
+                continue;
+            }
+            let trimmed = self.mode.trim
+                && c.is_whitespace()
+                && (first.is_none_or(|first| pos < first) || last.is_none_or(|last| pos > last));
+            if c == ESCAPE || trimmed || self.mode.tag_or_prefix_at(&name[pos..]) {
+                f.write_char(ESCAPE)?;
+            }
+            f.write_char(c)?;
+        }
+        Ok(())
+    }
+}
+impl Display for Bracket<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.list(f, self.nodes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::parser::{parse_complete, Brackets};
+
+    fn mode(delimiter: &str, (open, close): (&str, &str), trim: bool) -> ParseMode {
+        let brackets = Brackets {
+            open: open.to_owned(),
+            close: close.to_owned(),
+        };
+        ParseMode::new(delimiter.to_owned(), brackets, trim)
+    }
+
+    /// Parses `input`, writes it back and parses that again, returning the
+    /// written text after checking both parses agree.
+    fn round_trip(input: &str, mode: &ParseMode) -> String {
+        let parsed = parse_complete(input, mode.clone()).unwrap();
+        let written = Bracket::new(&parsed, mode).to_string();
+        assert_eq!(parse_complete(&written, mode.clone()).unwrap(), parsed);
+        written
+    }
+
+    fn names(input: &str, mode: &ParseMode) -> Vec<String> {
+        let parsed = parse_complete(input, mode.clone()).unwrap();
+        let root = Node::new(String::new(), parsed);
+        root.pre_order()
+            .skip(1)
+            .map(|(_, node)| node.name().to_owned())
+            .collect()
+    }
+
+    #[test]
+    fn plain() {
+        let mode = ParseMode::default();
+        assert_eq!(round_trip("a[b,c[d]],e", &mode), "a[b,c[d]],e");
+    }
+
+    #[test]
+    fn escaped_delimiters_and_brackets() {
+        let mode = ParseMode::default();
+        assert_eq!(names(r"a\,b[c\[d\]]", &mode), ["a,b", "c[d]"]);
+        assert_eq!(round_trip(r"a\,b[c\[d\]]", &mode), r"a\,b[c\[d\]]");
+    }
+
+    #[test]
+    fn backslashes() {
+        let mode = ParseMode::default();
+        assert_eq!(
+            names(r"C:\Users,x\\y,z\", &mode),
+            [r"C:\Users", r"x\y", r"z\"]
+        );
+        assert_eq!(
+            round_trip(r"C:\Users,x\\y,z\", &mode),
+            r"C:\\Users,x\\y,z\\"
+        );
+    }
+
+    #[test]
+    fn line_breaks() {
+        let mode = ParseMode::default();
+        assert_eq!(names(r"a\nb,c\\n", &mode), ["a\nb", r"c\n"]);
+        assert_eq!(round_trip(r"a\nb,c\\n", &mode), r"a\nb,c\\n");
+    }
+
+    #[test]
+    fn trimmed_whitespace() {
+        let mode = mode(",", ("[", "]"), true);
+        assert_eq!(names(r" a , \ b\ [ c ]", &mode), ["a", " b ", "c"]);
+        assert_eq!(round_trip(r" a , \ b\ [ c ]", &mode), r"a,\ b\ [c]");
+    }
+
+    #[test]
+    fn untrimmed_whitespace() {
+        let mode = ParseMode::default();
+        assert_eq!(round_trip(" a , b [ c ]", &mode), " a , b [ c ]");
+    }
+
+    #[test]
+    fn multi_character_delimiter() {
+        let mode = mode("::", ("[", "]"), true);
+        assert_eq!(names(r"a\: ::b", &mode), ["a:", "b"]);
+        assert_eq!(round_trip(r"a\: ::b", &mode), r"a\:::b");
+        assert_eq!(round_trip(r"a\::\:b::c", &mode), r"a\:\::b::c");
+    }
+
+    #[test]
+    fn multi_character_brackets() {
+        let mode = mode(",", ("<<", ">>"), false);
+        assert_eq!(names(r"x\<<<y\>>>,z", &mode), ["x<", "y>", "z"]);
+        assert_eq!(round_trip(r"x\<<<y\>>>,z", &mode), r"x\<<<y\>>>,z");
+        assert_eq!(round_trip(r"a<b,c>d", &mode), r"a<b,c>d");
+    }
+
+    #[test]
+    fn brackets_of_other_modes() {
+        let from = ParseMode::default();
+        let to = mode(";", ("(", ")"), false);
+        let parsed = parse_complete("a;b[c(d)],e", from).unwrap();
+        let written = Bracket::new(&parsed, &to).to_string();
+        assert_eq!(written, r"a\;b(c\(d\));e");
+        assert_eq!(parse_complete(&written, to).unwrap(), parsed);
+    }
+}
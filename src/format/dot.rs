@@ -0,0 +1,88 @@
+use std::fmt::{self, Display, Write};
+
+use crate::Node;
+
+/// Direction a graphviz graph is laid out in.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RankDir {
+    TopBottom,
+    BottomTop,
+    #[default]
+    LeftRight,
+    RightLeft,
+}
+impl RankDir {
+    /// Picks the direction matching the text layout with the same options.
+    pub fn from_layout(vertical: bool, invert: bool) -> Self {
+        match (vertical, invert) {
+            (true, false) => RankDir::TopBottom,
+            (true, true) => RankDir::BottomTop,
+            (false, false) => RankDir::LeftRight,
+            (false, true) => RankDir::RightLeft,
+        }
+    }
+}
+impl Display for RankDir {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            RankDir::TopBottom => "TB",
+            RankDir::BottomTop => "BT",
+            RankDir::LeftRight => "LR",
+            RankDir::RightLeft => "RL",
+        })
+    }
+}
+
+/// Writes trees as a graphviz digraph.
+///
+/// Nodes are numbered in pre-order, so ids only change
+/// when the structure before a node does.
+#[derive(Debug, Clone, Copy)]
+pub struct Dot<'a> {
+    nodes: &'a [Node],
+    rank_dir: RankDir,
+}
+impl<'a> Dot<'a> {
+    pub fn new(nodes: &'a [Node], rank_dir: RankDir) -> Self {
+        Dot { nodes, rank_dir }
+    }
+
+    /// Writes `node` and its descendants, returning the next free id.
+    fn node(f: &mut fmt::Formatter<'_>, node: &Node, id: usize) -> Result<usize, fmt::Error> {
+        write!(f, "  n{} [label=", id)?;
+        quoted(f, &node.name)?;
+        f.write_str("];\n")?;
+
+        let mut next = id + 1;
+        for child in &node.children {
+            writeln!(f, "  n{} -> n{};", id, next)?;
+            next = Dot::node(f, child, next)?;
+        }
+        Ok(next)
+    }
+}
+impl Display for Dot<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "digraph {{")?;
+        writeln!(f, "  rankdir={};", self.rank_dir)?;
+        let mut next = 0;
+        for node in self.nodes {
+            next = Dot::node(f, node, next)?;
+        }
+        f.write_str("}\n")
+    }
+}
+
+fn quoted(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => (),
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
@@ -0,0 +1,73 @@
+use std::fmt::{self, Display, Write};
+
+use super::escape_xml;
+use crate::Node;
+
+const STYLE: &str = "\
+.txtree,.txtree ul{list-style:none;margin:0;padding:0}\
+.txtree{font-family:monospace;line-height:1.5em}\
+.txtree summary,.txtree span{white-space:pre}\
+.txtree ul{margin-left:.5ch}\
+.txtree ul>li{position:relative;padding-left:2ch}\
+.txtree ul>li::before{content:\"\";position:absolute;left:0;top:0;\
+width:1.5ch;height:.75em;border-left:1px solid;border-bottom:1px solid}\
+.txtree ul>li:not(:last-child)::after{content:\"\";position:absolute;\
+left:0;top:0;bottom:0;border-left:1px solid}\
+.txtree summary{cursor:pointer;list-style:none}\
+.txtree summary::-webkit-details-marker{display:none}\
+.txtree summary::after{content:\" [+]\";opacity:.5}\
+.txtree details[open]>summary::after{content:\"\"}";
+
+/// Writes trees as a standalone html document.
+///
+/// Nodes with children become `<details>` elements, so their subtrees
+/// can be collapsed. Those shallower than `open_depth` start out expanded,
+/// all of them do if it is `None`.
+#[derive(Debug, Clone, Copy)]
+pub struct Html<'a> {
+    nodes: &'a [Node],
+    open_depth: Option<usize>,
+}
+impl<'a> Html<'a> {
+    pub fn new(nodes: &'a [Node], open_depth: Option<usize>) -> Self {
+        Html { nodes, open_depth }
+    }
+
+    fn node(&self, f: &mut fmt::Formatter<'_>, node: &Node, depth: usize) -> fmt::Result {
+        if node.children.is_empty() {
+            f.write_str("<li><span>")?;
+            escape_xml(f, &node.name)?;
+            return f.write_str("</span></li>\n");
+        }
+
+        match self.open_depth.is_none_or(|open| depth < open) {
+            true => f.write_str("<li><details open><summary>")?,
+            false => f.write_str("<li><details><summary>")?,
+        }
+        escape_xml(f, &node.name)?;
+        f.write_str("</summary>\n<ul>\n")?;
+        for child in &node.children {
+            self.node(f, child, depth + 1)?;
+        }
+        f.write_str("</ul></details></li>\n")
+    }
+}
+impl Display for Html<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("<!DOCTYPE html>\n<html>\n<head>\n<meta charset=\"utf-8\">\n")?;
+        let title = self
+            .nodes
+            .first()
+            .map_or("txtree", |node| node.name.as_str());
+        f.write_str("<title>")?;
+        escape_xml(f, title)?;
+        f.write_str("</title>\n<style>")?;
+        f.write_str(STYLE)?;
+        f.write_str("</style>\n</head>\n<body>\n<ul class=\"txtree\">\n")?;
+        for node in self.nodes {
+            self.node(f, node, 0)?;
+        }
+        f.write_str("</ul>\n</body>\n</html>")?;
+        f.write_char('\n')
+    }
+}
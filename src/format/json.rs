@@ -0,0 +1,50 @@
+use std::fmt::{self, Display, Write};
+
+use crate::Node;
+
+/// Writes a tree as a single line of JSON.
+///
+/// Every node becomes an object with a `name` string and a `children` array.
+#[derive(Debug, Clone, Copy)]
+pub struct Json<'a> {
+    node: &'a Node,
+}
+impl<'a> Json<'a> {
+    pub fn new(node: &'a Node) -> Self {
+        Json { node }
+    }
+
+    fn node(f: &mut fmt::Formatter<'_>, node: &Node) -> fmt::Result {
+        f.write_str("{\"name\":")?;
+        string(f, &node.name)?;
+        f.write_str(",\"children\":[")?;
+        for (index, child) in node.children.iter().enumerate() {
+            if index > 0 {
+                f.write_char(',')?;
+            }
+            Json::node(f, child)?;
+        }
+        f.write_str("]}")
+    }
+}
+impl Display for Json<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        Json::node(f, self.node)
+    }
+}
+
+fn string(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("\\\"")?,
+            '\\' => f.write_str("\\\\")?,
+            '\n' => f.write_str("\\n")?,
+            '\r' => f.write_str("\\r")?,
+            '\t' => f.write_str("\\t")?,
+            c if c.is_control() => write!(f, "\\u{:04x}", c as u32)?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
@@ -0,0 +1,91 @@
+use std::fmt::{self, Display, Write};
+
+use crate::Node;
+
+/// LaTeX package to draw trees with.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LatexDialect {
+    /// `forest` environment
+    #[default]
+    Forest,
+    /// `\Tree` of the `tikz-qtree` package
+    Qtree,
+}
+
+/// Writes trees as LaTeX, one environment per tree.
+#[derive(Debug, Clone, Copy)]
+pub struct Latex<'a> {
+    nodes: &'a [Node],
+    dialect: LatexDialect,
+}
+impl<'a> Latex<'a> {
+    pub fn new(nodes: &'a [Node], dialect: LatexDialect) -> Self {
+        Latex { nodes, dialect }
+    }
+
+    fn forest(f: &mut fmt::Formatter<'_>, node: &Node) -> fmt::Result {
+        f.write_str("[{")?;
+        escaped(f, &node.name)?;
+        f.write_char('}')?;
+        if node.name.contains('\n') {
+            f.write_str(", align=center")?;
+        }
+        for child in &node.children {
+            f.write_char(' ')?;
+            Latex::forest(f, child)?;
+        }
+        f.write_char(']')
+    }
+
+    fn qtree(f: &mut fmt::Formatter<'_>, node: &Node) -> fmt::Result {
+        if node.children.is_empty() {
+            f.write_char('{')?;
+            escaped(f, &node.name)?;
+            return f.write_char('}');
+        }
+        f.write_str("[.{")?;
+        escaped(f, &node.name)?;
+        f.write_char('}')?;
+        for child in &node.children {
+            f.write_char(' ')?;
+            Latex::qtree(f, child)?;
+        }
+        f.write_str(" ]")
+    }
+}
+impl Display for Latex<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for node in self.nodes {
+            match self.dialect {
+                LatexDialect::Forest => {
+                    f.write_str("\\begin{forest}\n")?;
+                    Latex::forest(f, node)?;
+                    f.write_str("\n\\end{forest}\n")?;
+                }
+                LatexDialect::Qtree => {
+                    f.write_str("\\begin{tikzpicture}\n\\Tree ")?;
+                    Latex::qtree(f, node)?;
+                    f.write_str("\n\\end{tikzpicture}\n")?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn escaped(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        match c {
+            '\\' => f.write_str("\\textbackslash{}")?,
+            '^' => f.write_str("\\textasciicircum{}")?,
+            '~' => f.write_str("\\textasciitilde{}")?,
+            '{' | '}' | '$' | '&' | '#' | '_' | '%' => {
+                f.write_char('\\')?;
+                f.write_char(c)?;
+            }
+            '\n' => f.write_str("\\\\")?,
+            c => f.write_char(c)?,
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,71 @@
+use std::fmt::{self, Display, Write};
+
+use super::RankDir;
+use crate::Node;
+
+/// Writes trees as a mermaid flowchart.
+///
+/// Nodes are numbered in pre-order, like in [`Dot`](super::Dot).
+#[derive(Debug, Clone, Copy)]
+pub struct Mermaid<'a> {
+    nodes: &'a [Node],
+    rank_dir: RankDir,
+}
+impl<'a> Mermaid<'a> {
+    pub fn new(nodes: &'a [Node], rank_dir: RankDir) -> Self {
+        Mermaid { nodes, rank_dir }
+    }
+
+    /// Writes `node` and its descendants, returning the next free id.
+    fn node(
+        f: &mut fmt::Formatter<'_>,
+        node: &Node,
+        id: usize,
+        parent: Option<usize>,
+    ) -> Result<usize, fmt::Error> {
+        match parent {
+            Some(parent) => write!(f, "  n{} --> n{}[", parent, id)?,
+            None => write!(f, "  n{}[", id)?,
+        }
+        quoted(f, &node.name)?;
+        f.write_str("]\n")?;
+
+        let mut next = id + 1;
+        for child in &node.children {
+            next = Mermaid::node(f, child, next, Some(id))?;
+        }
+        Ok(next)
+    }
+}
+impl Display for Mermaid<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let direction = match self.rank_dir {
+            RankDir::TopBottom => "TD",
+            RankDir::BottomTop => "BT",
+            RankDir::LeftRight => "LR",
+            RankDir::RightLeft => "RL",
+        };
+        writeln!(f, "graph {}", direction)?;
+        let mut next = 0;
+        for node in self.nodes {
+            next = Mermaid::node(f, node, next, None)?;
+        }
+        Ok(())
+    }
+}
+
+/// Quotes a label, replacing characters mermaid would read as markup.
+fn quoted(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    f.write_char('"')?;
+    for c in s.chars() {
+        match c {
+            '"' => f.write_str("#quot;")?,
+            '#' => f.write_str("#35;")?,
+            '<' => f.write_str("#lt;")?,
+            '>' => f.write_str("#gt;")?,
+            '\n' => f.write_str("<br>")?,
+            c => f.write_char(c)?,
+        }
+    }
+    f.write_char('"')
+}
@@ -0,0 +1,62 @@
+use std::fmt::{self, Display, Write};
+
+use crate::Node;
+
+/// Writes trees as a plantuml mind map.
+#[derive(Debug, Clone, Copy)]
+pub struct PlantUml<'a> {
+    nodes: &'a [Node],
+    vertical: bool,
+}
+impl<'a> PlantUml<'a> {
+    /// Creates the mind map, growing it downwards if `vertical` is set.
+    pub fn new(nodes: &'a [Node], vertical: bool) -> Self {
+        PlantUml { nodes, vertical }
+    }
+
+    fn node(f: &mut fmt::Formatter<'_>, node: &Node, depth: usize) -> fmt::Result {
+        (0..depth).try_for_each(|_| f.write_char('*'))?;
+        match node.name.contains('\n') {
+            true => {
+                f.write_char(':')?;
+                escaped(f, &node.name)?;
+                f.write_str(";\n")?;
+            }
+            false => {
+                f.write_char(' ')?;
+                escaped(f, &node.name)?;
+                f.write_char('\n')?;
+            }
+        }
+        for child in &node.children {
+            PlantUml::node(f, child, depth + 1)?;
+        }
+        Ok(())
+    }
+}
+impl Display for PlantUml<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("@startmindmap\n")?;
+        if self.vertical {
+            f.write_str("top to bottom direction\n")?;
+        }
+        for node in self.nodes {
+            PlantUml::node(f, node, 1)?;
+        }
+        f.write_str("@endmindmap\n")
+    }
+}
+
+/// Prefixes characters used by creole markup and node options with the escape `~`.
+fn escaped(f: &mut fmt::Formatter<'_>, s: &str) -> fmt::Result {
+    for c in s.chars() {
+        if matches!(
+            c,
+            '~' | '*' | '/' | '"' | '-' | '_' | '[' | ']' | '<' | '>' | ';' | '#'
+        ) {
+            f.write_char('~')?;
+        }
+        f.write_char(c)?;
+    }
+    Ok(())
+}
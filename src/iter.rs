@@ -0,0 +1,108 @@
+use std::collections::VecDeque;
+
+use crate::Node;
+
+/// Visits nodes before their children, see [`Node::pre_order`].
+#[derive(Debug, Clone)]
+pub struct PreOrder<'a> {
+    stack: Vec<(usize, &'a Node)>,
+}
+impl<'a> PreOrder<'a> {
+    pub(crate) fn new(node: &'a Node) -> Self {
+        PreOrder {
+            stack: vec![(0, node)],
+        }
+    }
+}
+impl<'a> Iterator for PreOrder<'a> {
+    type Item = (usize, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, node) = self.stack.pop()?;
+        self.stack
+            .extend(node.children.iter().rev().map(|c| (depth + 1, c)));
+        Some((depth, node))
+    }
+}
+
+/// Visits nodes after their children, see [`Node::post_order`].
+#[derive(Debug, Clone)]
+pub struct PostOrder<'a> {
+    stack: Vec<(&'a Node, usize)>,
+}
+impl<'a> PostOrder<'a> {
+    pub(crate) fn new(node: &'a Node) -> Self {
+        PostOrder {
+            stack: vec![(node, 0)],
+        }
+    }
+}
+impl<'a> Iterator for PostOrder<'a> {
+    type Item = (usize, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let depth = self.stack.len().checked_sub(1)?;
+            let (node, next) = self.stack.last_mut()?;
+            let node = *node;
+            match node.children.get(*next) {
+                Some(child) => {
+                    *next += 1;
+                    self.stack.push((child, 0));
+                }
+                None => {
+                    self.stack.pop();
+                    return Some((depth, node));
+                }
+            }
+        }
+    }
+}
+
+/// Visits nodes level by level, see [`Node::breadth_first`].
+#[derive(Debug, Clone)]
+pub struct BreadthFirst<'a> {
+    queue: VecDeque<(usize, &'a Node)>,
+}
+impl<'a> BreadthFirst<'a> {
+    pub(crate) fn new(node: &'a Node) -> Self {
+        BreadthFirst {
+            queue: VecDeque::from([(0, node)]),
+        }
+    }
+}
+impl<'a> Iterator for BreadthFirst<'a> {
+    type Item = (usize, &'a Node);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, node) = self.queue.pop_front()?;
+        self.queue
+            .extend(node.children.iter().map(|c| (depth + 1, c)));
+        Some((depth, node))
+    }
+}
+
+/// Visits nodes in pre-order along with their ancestors, see [`Node::paths`].
+#[derive(Debug, Clone)]
+pub struct Paths<'a> {
+    path: Vec<&'a Node>,
+    pending: PreOrder<'a>,
+}
+impl<'a> Paths<'a> {
+    pub(crate) fn new(node: &'a Node) -> Self {
+        Paths {
+            path: Vec::new(),
+            pending: PreOrder::new(node),
+        }
+    }
+}
+impl<'a> Iterator for Paths<'a> {
+    type Item = Vec<&'a Node>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (depth, node) = self.pending.next()?;
+        self.path.truncate(depth);
+        self.path.push(node);
+        Some(self.path.clone())
+    }
+}
@@ -1,15 +1,34 @@
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{self, Display, Write},
+    str::FromStr,
+};
+
+use iter::{BreadthFirst, Paths, PostOrder, PreOrder};
+use parser::{parse_complete, ParseError, ParseMode};
+use render::{AlignmentBuilder, RenderMode, SymbolsBuilder};
+use sort::Shape;
+
+pub mod diff;
+pub mod format;
+pub mod iter;
 pub mod parser;
 pub mod render;
+mod select;
+mod sort;
 
 pub mod prelude {
     pub use super::{
-        parser::{parse_nodes, Brackets, ParseMode},
+        format::{Bracket, Dot, Html, Json, Latex, LatexDialect, Mermaid, PlantUml, RankDir},
+        parser::{parse_complete, parse_nodes, Brackets, ParseError, ParseMode},
         render::{AlignmentBuilder, RenderMode, SymbolsBuilder},
         Node, OrderModeBuilder,
     };
 }
 
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Node {
     name: String,
     children: Vec<Node>,
@@ -18,25 +37,320 @@ impl Node {
     pub fn new(name: String, children: Vec<Node>) -> Self {
         Node { name, children }
     }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    pub fn name_mut(&mut self) -> &mut String {
+        &mut self.name
+    }
+    pub fn children(&self) -> &[Node] {
+        &self.children
+    }
+    pub fn children_mut(&mut self) -> &mut Vec<Node> {
+        &mut self.children
+    }
+
+    /// Iterates over this node and its descendants, parents first.
+    ///
+    /// Items are paired with their depth below this node.
+    pub fn pre_order(&self) -> PreOrder<'_> {
+        PreOrder::new(self)
+    }
+    /// Iterates over this node and its descendants, children first.
+    ///
+    /// Items are paired with their depth below this node.
+    pub fn post_order(&self) -> PostOrder<'_> {
+        PostOrder::new(self)
+    }
+    /// Iterates over this node and its descendants, one depth at a time.
+    ///
+    /// Items are paired with their depth below this node.
+    pub fn breadth_first(&self) -> BreadthFirst<'_> {
+        BreadthFirst::new(self)
+    }
+    /// Iterates over this node and its descendants in pre-order.
+    ///
+    /// Each item lists the nodes from this one down to the visited node.
+    pub fn paths(&self) -> Paths<'_> {
+        Paths::new(self)
+    }
+
+    /// Looks up a descendant by the names leading to it, starting with a child.
+    pub fn find(&self, path: &[&str]) -> Option<&Node> {
+        path.iter().try_fold(self, |node, name| {
+            node.children.iter().find(|c| c.name == *name)
+        })
+    }
+    /// Finds descendants by a `/`-separated path of names, starting with a child.
+    ///
+    /// Within a name, `*` matches any number of characters and `?` exactly one.
+    /// A `**` segment matches any number of levels. Results are in pre-order,
+    /// each listed once. This node is never included, so a selector without
+    /// names finds nothing.
+    pub fn select(&self, selector: &str) -> Vec<&Node> {
+        let segments = selector
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<_>>();
+        let mut found = Vec::new();
+        select::select(self, &segments, &mut found);
+        found
+    }
+
     pub fn order_children(&mut self, order_mode: &OrderMode) {
+        self.order_below(order_mode);
+    }
+    /// Orders the children of every node, deepest first, returning the shape
+    /// of this node so each subtree is only measured once.
+    fn order_below(&mut self, order_mode: &OrderMode) -> Shape {
+        let shapes = self
+            .children
+            .iter_mut()
+            .map(|c| c.order_below(order_mode))
+            .collect::<Vec<_>>();
+        let shape = Shape::above(&shapes);
+
         match order_mode.sort.as_ref() {
             Some(Sorter::Alphabetical) => self.children.sort(),
             Some(Sorter::External(prog)) => todo!("Sort using {}", prog),
+            Some(Sorter::Keys(keys)) => {
+                let mut keyed = shapes
+                    .into_iter()
+                    .zip(std::mem::take(&mut self.children))
+                    .collect::<Vec<_>>();
+                keyed.sort_by(|a, b| SortKey::compare_all(keys, a, b));
+                self.children = keyed.into_iter().map(|(_, c)| c).collect();
+            }
             None => (),
         }
 
         if order_mode.reverse {
             self.children.reverse();
         }
-
+        shape
+    }
+    /// Keeps only descendants whose names match, along with their ancestors.
+    ///
+    /// With `subtrees`, everything below a matching descendant is kept too.
+    /// Returns whether any descendant matched.
+    pub fn filter(&mut self, matches: &impl Fn(&str) -> bool, subtrees: bool) -> bool {
         self.children
+            .retain_mut(|child| match matches(&child.name) {
+                true if subtrees => true,
+                true => {
+                    child.filter(matches, subtrees);
+                    true
+                }
+                false => child.filter(matches, subtrees),
+            });
+        !self.children.is_empty()
+    }
+    /// Removes descendants whose names match, along with everything below them.
+    pub fn exclude(&mut self, matches: &impl Fn(&str) -> bool) {
+        self.children.retain(|child| !matches(&child.name));
+        self.children.iter_mut().for_each(|c| c.exclude(matches));
+    }
+    /// Merges children sharing a name into the first of them, recursively.
+    ///
+    /// Children of merged nodes are merged the same way, keeping their order.
+    pub fn merge_siblings(&mut self) {
+        let mut merged: Vec<Node> = Vec::with_capacity(self.children.len());
+        let mut index = HashMap::<String, usize>::new();
+        for child in std::mem::take(&mut self.children) {
+            match index.get(&child.name) {
+                Some(&at) => merged[at].children.extend(child.children),
+                None => {
+                    index.insert(child.name.clone(), merged.len());
+                    merged.push(child);
+                }
+            }
+        }
+        self.children = merged;
+        self.children.iter_mut().for_each(Node::merge_siblings);
+    }
+    /// Adds the children of `other` to those of this node,
+    /// merging nodes that share a name as in [`Node::merge_siblings`].
+    pub fn merge(&mut self, other: Node) {
+        self.children.extend(other.children);
+        self.merge_siblings();
+    }
+    /// Merges descendants that have a single child with that child,
+    /// joining their names with `joiner`.
+    ///
+    /// Undone by [`Node::expand`], unless names already contained `joiner`.
+    pub fn collapse(&mut self, joiner: &str) {
+        for child in &mut self.children {
+            while let [_] = child.children.as_slice() {
+                let only = child.children.remove(0);
+                child.name.push_str(joiner);
+                child.name.push_str(&only.name);
+                child.children = only.children;
+            }
+            child.collapse(joiner);
+        }
+    }
+    /// Splits the names of descendants at `joiner` into chains of single children.
+    pub fn expand(&mut self, joiner: &str) {
+        for child in &mut self.children {
+            child.expand(joiner);
+            if joiner.is_empty() || !child.name.contains(joiner) {
+                continue;
+            }
+            let mut parts = child
+                .name
+                .split(joiner)
+                .map(String::from)
+                .collect::<Vec<_>>();
+            let mut children = std::mem::take(&mut child.children);
+            while parts.len() > 1 {
+                let name = parts.pop().expect("Split should leave a part");
+                children = vec![Node::new(name, children)];
+            }
+            child.name = parts.remove(0);
+            child.children = children;
+        }
+    }
+    /// Appends `annotations` to the names of this node and its descendants,
+    /// in the given order. Counts of zero are left out.
+    pub fn annotate(&mut self, annotations: &[Annotation]) {
+        self.annotate_at(0, annotations);
+    }
+    /// Returns the number of descendants.
+    fn annotate_at(&mut self, depth: usize, annotations: &[Annotation]) -> usize {
+        let descendants = self
+            .children
             .iter_mut()
-            .for_each(|c| c.order_children(order_mode));
+            .map(|c| 1 + c.annotate_at(depth + 1, annotations))
+            .sum();
+        for annotation in annotations {
+            let _ = match annotation {
+                Annotation::Children if !self.children.is_empty() => {
+                    write!(self.name, " ({})", self.children.len())
+                }
+                Annotation::Descendants if descendants > 0 => {
+                    write!(self.name, " [{}]", descendants)
+                }
+                Annotation::Depth => write!(self.name, " @{}", depth),
+                _ => Ok(()),
+            };
+        }
+        descendants
+    }
+    /// Removes descendants more than `max_depth` levels below this node.
+    ///
+    /// Nodes that lose their children get a single child named `marker`
+    /// instead, with `{}` replaced by the number of removed descendants.
+    pub fn prune(&mut self, max_depth: usize, marker: Option<&str>) {
+        if max_depth > 0 {
+            self.children
+                .iter_mut()
+                .for_each(|c| c.prune(max_depth - 1, marker));
+            return;
+        }
+        if self.children.is_empty() {
+            return;
+        }
+        let hidden = self.pre_order().count() - 1;
+        self.children.clear();
+        if let Some(marker) = marker {
+            let name = marker.replace("{}", &hidden.to_string());
+            self.children.push(Node::new(name, Vec::new()));
+        }
     }
     pub fn take_children(self) -> Vec<Node> {
         self.children
     }
+
+    /// Draws the children of this node with rounded symbols, leaving out the node itself.
+    #[deprecated(note = "use `RenderMode::render` or `Display` instead")]
+    pub fn mock_display(&self) -> String {
+        let render_mode = RenderMode::new(
+            SymbolsBuilder::new().default_to_rounded(true).build(),
+            0,
+            0,
+            false,
+            false,
+            false,
+            AlignmentBuilder::new().build(),
+        );
+        let mut out = String::new();
+        render_mode
+            .render(self, &mut out)
+            .expect("Writing to a String should not fail");
+        out.lines()
+            .skip(self.name.split('\n').count())
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
 }
+impl FromStr for Node {
+    type Err = ParseError;
+
+    /// Parses a single tree using the default [`ParseMode`].
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut nodes = parse_complete(s, ParseMode::default())?;
+        match nodes.len() {
+            1 => Ok(nodes.remove(0)),
+            n => Err(ParseError::NotSingle(n)),
+        }
+    }
+}
+
+/// Renders the tree using the default [`RenderMode`].
+///
+/// Formatting flags adjust the output:
+/// - `{:#}` uses the ascii symbol set
+/// - `{:^}` branches out on both sides, `{:>}` grows the tree to the left
+/// - `{:N}` puts N columns between elements
+/// - `{:.N}` puts N lines between elements
+impl Display for Node {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let to_u8 = |n: Option<usize>| n.map_or(0, |n| u8::try_from(n).unwrap_or(u8::MAX));
+        let render_mode = RenderMode::new(
+            SymbolsBuilder::new()
+                .default_to_ascii(f.alternate())
+                .build(),
+            to_u8(f.precision()),
+            to_u8(f.width()),
+            false,
+            false,
+            matches!(f.align(), Some(fmt::Alignment::Right)),
+            AlignmentBuilder::new()
+                .centered(matches!(f.align(), Some(fmt::Alignment::Center)))
+                .build(),
+        );
+        render_mode.render(
+            self,
+            &mut TrimNewline {
+                out: f,
+                pending: false,
+            },
+        )
+    }
+}
+
+/// Drops the newline at the very end of the written text.
+struct TrimNewline<W> {
+    out: W,
+    pending: bool,
+}
+impl<W: Write> Write for TrimNewline<W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.is_empty() {
+            return Ok(());
+        }
+        if self.pending {
+            self.out.write_char('\n')?;
+        }
+        self.pending = s.ends_with('\n');
+        match self.pending {
+            true => self.out.write_str(&s[..s.len() - 1]),
+            false => self.out.write_str(s),
+        }
+    }
+}
+
 impl<S, I> From<(S, I)> for Node
 where
     String: From<S>,
@@ -47,6 +361,17 @@ where
     }
 }
 
+/// Information appended to names by [`Node::annotate`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Annotation {
+    /// Number of children, like `(3)`
+    Children,
+    /// Number of descendants, like `[17]`
+    Descendants,
+    /// Depth below the annotated node, like `@2`
+    Depth,
+}
+
 #[derive(Debug, Clone)]
 pub struct OrderMode {
     sort: Option<Sorter>,
@@ -55,12 +380,89 @@ pub struct OrderMode {
 #[derive(Debug, Clone)]
 pub enum Sorter {
     Alphabetical,
+    /// Each key in turn, moving on to the next one on ties.
+    /// Nodes equal by every key keep their order.
+    Keys(Vec<SortKey>),
     External(String),
 }
 
+/// Criterion siblings can be compared by.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Names by character
+    Alphabetical,
+    /// Numbers within names by value, like `item2` before `item10`
+    Natural,
+    /// By the number names start with, names without one last
+    Numeric,
+    /// Alphabetical, ignoring case
+    CaseInsensitive,
+    /// Shorter names first
+    Length,
+    /// Version numbers, like `1.9` before `1.10` before `2.0-rc1` before `2.0`
+    Version,
+    /// Number of children, so leaves go first
+    Children,
+    /// Number of descendants
+    Descendants,
+    /// Number of levels below
+    Height,
+    /// Nodes without children before those with children
+    LeavesFirst,
+}
+impl SortOrder {
+    /// Compares nodes paired with their shape.
+    fn compare(self, (a_shape, a): &(Shape, Node), (b_shape, b): &(Shape, Node)) -> Ordering {
+        match self {
+            SortOrder::Alphabetical => a.name.cmp(&b.name),
+            SortOrder::Natural => sort::natural(&a.name, &b.name),
+            SortOrder::Numeric => sort::numeric(&a.name, &b.name),
+            SortOrder::CaseInsensitive => sort::case_insensitive(&a.name, &b.name),
+            SortOrder::Length => sort::length(&a.name, &b.name),
+            SortOrder::Version => sort::version(&a.name, &b.name),
+            SortOrder::Children => a_shape.children.cmp(&b_shape.children),
+            SortOrder::Descendants => a_shape.descendants.cmp(&b_shape.descendants),
+            SortOrder::Height => a_shape.height.cmp(&b_shape.height),
+            SortOrder::LeavesFirst => (!a.children.is_empty()).cmp(&!b.children.is_empty()),
+        }
+    }
+}
+
+/// Criterion of a [`Sorter::Keys`] sort.
+#[derive(Debug, Clone, Copy)]
+pub struct SortKey {
+    pub order: SortOrder,
+    pub direction: Direction,
+}
+impl SortKey {
+    /// Compares by every key in turn.
+    fn compare_all(keys: &[SortKey], a: &(Shape, Node), b: &(Shape, Node)) -> Ordering {
+        keys.iter().fold(Ordering::Equal, |order, key| {
+            order.then_with(|| key.direction.apply(key.order.compare(a, b)))
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Direction {
+    #[default]
+    Ascending,
+    Descending,
+}
+impl Direction {
+    fn apply(self, order: Ordering) -> Ordering {
+        match self {
+            Direction::Ascending => order,
+            Direction::Descending => order.reverse(),
+        }
+    }
+}
+
 #[derive(Debug, Clone, Default)]
 pub struct OrderModeBuilder {
     sort_program: Option<String>,
+    sorter: Option<Sorter>,
+    keys: Vec<SortKey>,
     default_to_alphabetical: bool,
     reverse_order: bool,
 }
@@ -77,17 +479,38 @@ impl OrderModeBuilder {
         self.sort_program = program;
         self
     }
+    /// Sorts with `sorter`, unless a sort program is set.
+    pub fn opt_sorter(&mut self, sorter: Option<Sorter>) -> &mut Self {
+        self.sorter = sorter;
+        self
+    }
+    /// Adds sort keys, each used for nodes the previous keys consider equal.
+    ///
+    /// Keys are ignored when a sort program or sorter is set.
+    pub fn sort_keys(
+        &mut self,
+        keys: impl IntoIterator<Item = (SortOrder, Direction)>,
+    ) -> &mut Self {
+        self.keys.extend(
+            keys.into_iter()
+                .map(|(order, direction)| SortKey { order, direction }),
+        );
+        self
+    }
     pub fn reverse(&mut self, reverse: bool) -> &mut Self {
         self.reverse_order = reverse;
         self
     }
 
     pub fn build(&self) -> OrderMode {
+        let keys = (!self.keys.is_empty()).then(|| Sorter::Keys(self.keys.clone()));
         OrderMode {
             sort: self
                 .sort_program
                 .as_ref()
                 .map(|p| Sorter::External(p.clone()))
+                .or_else(|| self.sorter.clone())
+                .or(keys)
                 .or(match self.default_to_alphabetical {
                     true => Some(Sorter::Alphabetical),
                     false => None,
@@ -96,84 +519,3 @@ impl OrderModeBuilder {
         }
     }
 }
-
-enum DisplayPlace {
-    Item,
-    Last,
-}
-
-const PRE_LINE: &str = "│ ";
-const PRE_ITEM: &str = "├╴";
-const PRE_LAST: &str = "╰╴";
-const PRE_EMPT: &str = "  ";
-
-impl Node {
-    pub fn mock_display(&self) -> String {
-        self.display_root("")
-    }
-
-    fn display_root(&self, preprint: &str) -> String {
-        let root_length = self.children.len();
-        self.children
-            .iter()
-            .enumerate()
-            .map(|(index, child)| {
-                child.display_branch(
-                    // FMT: -
-                    root_length,
-                    index,
-                    preprint,
-                )
-            })
-            .collect::<Vec<_>>()
-            .join("\n")
-    }
-
-    fn display_branch(&self, root_len: usize, index: usize, preprint: &str) -> String {
-        let len = self.children.len();
-        let placement = compute_placement(root_len, index);
-        let child_preprint = compute_child_preprint(preprint, &placement);
-        let preprint = compute_preprint(preprint, &placement);
-        let name_separator = compute_name_separator(len);
-
-        format!(
-            "{}{}{}{}",
-            preprint,
-            self.name,
-            name_separator,
-            self.display_root(&child_preprint)
-        )
-    }
-}
-
-fn compute_placement(root_len: usize, index: usize) -> DisplayPlace {
-    let last_index = root_len.saturating_sub(1);
-    if index == last_index {
-        DisplayPlace::Last
-    } else {
-        DisplayPlace::Item
-    }
-}
-
-fn compute_child_preprint(preprint: &str, placement: &DisplayPlace) -> String {
-    use DisplayPlace::*;
-    match placement {
-        Item => format!("{}{}", preprint, PRE_LINE),
-        Last => format!("{}{}", preprint, PRE_EMPT),
-    }
-}
-
-fn compute_preprint(preprint: &str, placement: &DisplayPlace) -> String {
-    use DisplayPlace::*;
-    match placement {
-        Item => format!("{}{}", preprint, PRE_ITEM),
-        Last => format!("{}{}", preprint, PRE_LAST),
-    }
-}
-
-fn compute_name_separator(len: usize) -> &'static str {
-    match len {
-        0 => "",
-        _ => "\n",
-    }
-}
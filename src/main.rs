@@ -1,12 +1,19 @@
 use std::{
-    io::{self, BufRead},
+    env,
+    io::{self, BufRead, BufWriter, IsTerminal, Write},
     process::exit,
 };
 
 use clap::Parser;
-use txtree::{prelude::*, OrderMode};
+use regex::Regex;
+use txtree::{
+    diff::diff,
+    prelude::*,
+    render::{ColorsBuilder, Overflow, SvgStyle, SvgStyleBuilder},
+    Annotation, Direction, OrderMode, SortOrder,
+};
 
-use crate::opt::Opt;
+use crate::opt::{AnnotationOpt, ColorChoice, Command, FormatOut, Opt, OverflowOpt, SortOpt};
 
 mod opt;
 
@@ -21,13 +28,27 @@ macro_rules! print_debug {
 fn main() {
     let opt = Opt::parse();
 
+    let write_mode = ParseMode::new(
+        opt.delimiter_out.unwrap_or_else(|| opt.delimiter.clone()),
+        opt.brackets_out.unwrap_or_else(|| opt.brackets.clone()),
+        opt.trim,
+    );
     let parse_mode = ParseMode::new(opt.delimiter, opt.brackets, opt.trim);
 
-    let order_mode = OrderModeBuilder::new()
-        .opt_sort_program(opt.sort_by)
-        .default_to_alphabetical(opt.sort)
-        .reverse(opt.reverse)
-        .build();
+    let colors = use_color(opt.color).then(|| {
+        ColorsBuilder::new()
+            .connector(opt.color_connector)
+            .depths(opt.color_depths)
+            .leaf(opt.color_leaf)
+            .internal(opt.color_internal)
+            .build()
+    });
+
+    let (width, overflow) = match opt.overflow {
+        OverflowOpt::Truncate => (terminal_width(opt.width), Overflow::Truncate),
+        OverflowOpt::Wrap => (terminal_width(opt.width), Overflow::Wrap),
+        OverflowOpt::None => (None, Overflow::default()),
+    };
 
     let render_mode = RenderMode::new(
         SymbolsBuilder::new()
@@ -44,50 +65,280 @@ fn main() {
             .flipped(opt.flip)
             .centered(opt.middle)
             .build(),
-    );
+    )
+    .with_colors(colors)
+    .with_width(width, overflow);
+
+    let writer = match opt.format_out {
+        FormatOut::Tree => Writer::Tree(render_mode),
+        FormatOut::Bracket => Writer::Bracket(write_mode),
+        FormatOut::Json => Writer::Json,
+        FormatOut::Svg => Writer::Svg(
+            render_mode,
+            SvgStyleBuilder::new()
+                .font_family(opt.svg_font)
+                .font_size(opt.svg_font_size)
+                .column_width(opt.svg_column_width)
+                .row_height(opt.svg_row_height)
+                .stroke(opt.svg_stroke)
+                .stroke_width(opt.svg_stroke_width)
+                .build(),
+        ),
+        FormatOut::Html => Writer::Html(opt.open_depth),
+        FormatOut::Dot => Writer::Dot(RankDir::from_layout(opt.vertical, opt.invert)),
+        FormatOut::Mermaid => Writer::Mermaid(RankDir::from_layout(opt.vertical, opt.invert)),
+        FormatOut::Plantuml => Writer::PlantUml(opt.vertical),
+        FormatOut::Forest => Writer::Latex(LatexDialect::Forest),
+        FormatOut::TikzQtree => Writer::Latex(LatexDialect::Qtree),
+    };
+
+    let transform = Transform {
+        order_mode: OrderModeBuilder::new()
+            .opt_sort_program(opt.sort_by)
+            .sort_keys(opt.sort.iter().map(|key| {
+                let order = match key.order {
+                    SortOpt::Alphabetical => SortOrder::Alphabetical,
+                    SortOpt::Natural => SortOrder::Natural,
+                    SortOpt::Numeric => SortOrder::Numeric,
+                    SortOpt::Ci => SortOrder::CaseInsensitive,
+                    SortOpt::Length => SortOrder::Length,
+                    SortOpt::Version => SortOrder::Version,
+                    SortOpt::Children => SortOrder::Children,
+                    SortOpt::Descendants => SortOrder::Descendants,
+                    SortOpt::Height => SortOrder::Height,
+                    SortOpt::LeavesFirst => SortOrder::LeavesFirst,
+                };
+                let direction = match key.descending {
+                    true => Direction::Descending,
+                    false => Direction::Ascending,
+                };
+                (order, direction)
+            }))
+            .reverse(opt.reverse)
+            .build(),
+        merge: opt.merge,
+        select: opt.select,
+        filter: opt.filter,
+        filter_subtree: opt.filter_subtree,
+        exclude: opt.exclude,
+        collapse: opt.collapse.filter(|_| !writer.serializes()),
+        annotations: opt
+            .annotate
+            .iter()
+            .map(|annotation| match annotation {
+                AnnotationOpt::Children => Annotation::Children,
+                AnnotationOpt::Descendants => Annotation::Descendants,
+                AnnotationOpt::Depth => Annotation::Depth,
+            })
+            .collect(),
+        max_depth: opt.max_depth,
+        elision: Some(opt.elision).filter(|e| !e.is_empty() && !writer.serializes()),
+    };
 
     print_debug!(opt.debug, parse_mode);
-    print_debug!(opt.debug, order_mode);
-    print_debug!(opt.debug, render_mode);
+    print_debug!(opt.debug, transform);
+    print_debug!(opt.debug, writer);
 
-    if opt.input.is_empty() {
+    let stdout = io::stdout();
+    let mut out = BufWriter::new(stdout.lock());
+
+    let result = if let Some(Command::Diff { old, new }) = &opt.command {
+        let parse = |input: &str| {
+            parse_input(input, parse_mode.clone()).unwrap_or_else(|e| {
+                eprintln!("{}", e);
+                exit(1)
+            })
+        };
+        let old = transform.apply(parse(old), opt.debug);
+        let new = transform.apply(parse(new), opt.debug);
+        write_trees(&mut out, &diff(&old, &new), &writer)
+    } else if opt.input.is_empty() {
         let stdin = io::stdin();
-        for line in stdin.lock().lines() {
-            let line = line.expect("Failed to read from stdin");
-            match render_string(&line, parse_mode.clone(), &order_mode, opt.debug) {
-                Ok(s) => println!("{}", s),
-                Err(e) => eprintln!("{}", e),
-            };
-        }
+        let mut merged = Vec::new();
+        let mut buffered = Vec::new();
+        stdin
+            .lock()
+            .lines()
+            .try_for_each(|line| {
+                let line = line.expect("Failed to read from stdin");
+                match parse_input(&line, parse_mode.clone()) {
+                    Ok(nodes) if transform.merge => {
+                        merged.extend(nodes);
+                        Ok(())
+                    }
+                    Ok(nodes) if writer.single_document() => {
+                        buffered.extend(transform.apply(nodes, opt.debug));
+                        Ok(())
+                    }
+                    Ok(nodes) => {
+                        let nodes = transform.apply(nodes, opt.debug);
+                        write_trees(&mut out, &nodes, &writer)
+                    }
+                    Err(e) => {
+                        out.flush()?;
+                        eprintln!("{}", e);
+                        Ok(())
+                    }
+                }
+            })
+            .and_then(|()| {
+                if !merged.is_empty() {
+                    buffered = transform.apply(merged, opt.debug);
+                }
+                match buffered.is_empty() {
+                    true => Ok(()),
+                    false => write_trees(&mut out, &buffered, &writer),
+                }
+            })
     } else {
-        match render_string(&opt.input.join(" "), parse_mode, &order_mode, opt.debug) {
-            Ok(s) => println!("{}", s),
+        match parse_input(&opt.input.join(" "), parse_mode) {
+            Ok(nodes) => write_trees(&mut out, &transform.apply(nodes, opt.debug), &writer),
             Err(e) => {
                 eprintln!("{}", e);
                 exit(1)
             }
         }
+    };
+
+    match result {
+        Ok(()) => (),
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => (),
+        Err(e) => {
+            eprintln!("Failed to write output. {}", e);
+            exit(1)
+        }
     }
 }
 
-fn render_string(
-    input: &str,
-    parse_mode: ParseMode,
-    order_mode: &OrderMode,
-    debug: bool,
-) -> Result<String, String> {
-    let mut parsed = Node::new(
-        String::from("ROOT"),
-        match parse_nodes(input, parse_mode) {
-            Ok(("", p)) => p,
-            Ok((s, _)) => return Err(format!("Failed to parse input, on {:?}.", s)),
-            Err(e) => return Err(format!("Failed to parse input. {:?}", e)),
-        },
-    );
+fn use_color(choice: ColorChoice) -> bool {
+    match choice {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            io::stdout().is_terminal() && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty())
+        }
+    }
+}
+
+fn terminal_width(width: Option<usize>) -> Option<usize> {
+    width.or_else(|| {
+        terminal_size::terminal_size_of(io::stdout()).map(|(width, _)| width.0 as usize)
+    })
+}
 
-    print_debug!(debug, parsed);
-    parsed.order_children(order_mode);
-    print_debug!(debug, parsed);
+fn parse_input(input: &str, parse_mode: ParseMode) -> Result<Vec<Node>, ParseError> {
+    parse_complete(input, parse_mode)
+}
 
-    Ok(parsed.mock_display())
+/// Changes made to the parsed trees before writing them.
+#[derive(Debug)]
+struct Transform {
+    order_mode: OrderMode,
+    merge: bool,
+    select: Vec<String>,
+    filter: Option<Regex>,
+    filter_subtree: bool,
+    exclude: Option<Regex>,
+    collapse: Option<String>,
+    annotations: Vec<Annotation>,
+    max_depth: Option<usize>,
+    elision: Option<String>,
+}
+impl Transform {
+    /// Applies all changes to the parsed trees.
+    fn apply(&self, nodes: Vec<Node>, debug: bool) -> Vec<Node> {
+        let mut root = Node::new(String::from("ROOT"), nodes);
+        print_debug!(debug, root);
+
+        if self.merge {
+            root.merge_siblings();
+        }
+        if !self.select.is_empty() {
+            let selected = self
+                .select
+                .iter()
+                .flat_map(|path| root.select(path))
+                .cloned()
+                .collect();
+            root = Node::new(String::from("ROOT"), selected);
+        }
+        if let Some(exclude) = &self.exclude {
+            root.exclude(&|name| exclude.is_match(name));
+        }
+        if let Some(filter) = &self.filter {
+            root.filter(&|name| filter.is_match(name), self.filter_subtree);
+        }
+        root.order_children(&self.order_mode);
+        if let Some(joiner) = &self.collapse {
+            root.collapse(joiner);
+        }
+        if !self.annotations.is_empty() {
+            root.children_mut()
+                .iter_mut()
+                .for_each(|c| c.annotate(&self.annotations));
+        }
+        if let Some(max_depth) = self.max_depth {
+            root.children_mut()
+                .iter_mut()
+                .for_each(|c| c.prune(max_depth, self.elision.as_deref()));
+        }
+
+        print_debug!(debug, root);
+        root.take_children()
+    }
+}
+
+#[derive(Debug)]
+enum Writer {
+    Tree(RenderMode),
+    Bracket(ParseMode),
+    Json,
+    Svg(RenderMode, SvgStyle),
+    Html(Option<usize>),
+    Dot(RankDir),
+    Mermaid(RankDir),
+    PlantUml(bool),
+    Latex(LatexDialect),
+}
+
+impl Writer {
+    /// Whether the output is meant to be read back in, rather than looked at.
+    fn serializes(&self) -> bool {
+        matches!(self, Writer::Bracket(_) | Writer::Json)
+    }
+
+    /// Whether all trees have to be written at once to form a valid file.
+    fn single_document(&self) -> bool {
+        matches!(
+            self,
+            Writer::Svg(..)
+                | Writer::Html(_)
+                | Writer::Dot(_)
+                | Writer::Mermaid(_)
+                | Writer::PlantUml(_)
+        )
+    }
+}
+
+fn write_trees(out: &mut impl Write, nodes: &[Node], writer: &Writer) -> io::Result<()> {
+    match writer {
+        Writer::Tree(render_mode) => {
+            for node in nodes {
+                render_mode.render_to(node, out)?;
+            }
+        }
+        Writer::Bracket(mode) => writeln!(out, "{}", Bracket::new(nodes, mode))?,
+        Writer::Json => {
+            for node in nodes {
+                writeln!(out, "{}", Json::new(node))?;
+            }
+        }
+        Writer::Svg(render_mode, style) => render_mode.render_svg_to(nodes, style, out)?,
+        Writer::Html(open_depth) => write!(out, "{}", Html::new(nodes, *open_depth))?,
+        Writer::Dot(rank_dir) => write!(out, "{}", Dot::new(nodes, *rank_dir))?,
+        Writer::Mermaid(rank_dir) => write!(out, "{}", Mermaid::new(nodes, *rank_dir))?,
+        Writer::PlantUml(vertical) => write!(out, "{}", PlantUml::new(nodes, *vertical))?,
+        Writer::Latex(dialect) => write!(out, "{}", Latex::new(nodes, *dialect))?,
+    }
+    out.flush()
 }
@@ -1,11 +1,22 @@
-use clap::Parser;
-use txtree::{parser::Brackets, render::Symbols};
+use clap::{Parser, Subcommand, ValueEnum};
+use regex::Regex;
+use txtree::{
+    parser::Brackets,
+    render::{Style, Symbols},
+};
 
 /// Utility to generate text-based tree graphs
 #[derive(Parser)]
 #[command(author, version, about, long_about = None)]
 pub(crate) struct Opt {
+    #[command(subcommand)]
+    pub(crate) command: Option<Command>,
+
     /// Strings to be turned into a graph
+    ///
+    /// Use a backslash to escape delimiters, brackets, whitespace and
+    /// backslashes within names. A backslash followed by "n" breaks a name
+    /// into lines. Other backslashes are kept.
     pub(crate) input: Vec<String>,
 
     /// String used to separate elements
@@ -31,6 +42,29 @@ pub(crate) struct Opt {
     #[arg(short, long)]
     pub(crate) trim: bool,
 
+    /// Format to write the parsed input in
+    #[arg(
+        long,
+        visible_alias = "output-format",
+        value_enum,
+        default_value_t = FormatOut::Tree
+    )]
+    pub(crate) format_out: FormatOut,
+
+    /// String used to separate elements in the output
+    ///
+    /// Defaults to --delimiter.
+    /// Only used with --format-out bracket.
+    #[arg(long, name = "DELIMITER")]
+    pub(crate) delimiter_out: Option<String>,
+
+    /// Strings used to enclose child elements in the output
+    ///
+    /// Defaults to --brackets.
+    /// Only used with --format-out bracket.
+    #[arg(long, name = "BRACKETS")]
+    pub(crate) brackets_out: Option<Brackets>,
+
     /// Number of lines between elements
     #[arg(short, long, default_value_t = 0)]
     pub(crate) lines: u8,
@@ -97,11 +131,172 @@ pub(crate) struct Opt {
     #[arg(short, long, verbatim_doc_comment)]
     pub(crate) symbols: Option<Symbols>,
 
-    /// Sort child elements alphabetically
+    /// Number of columns to fit the tree into
+    ///
+    /// Defaults to the width of the terminal, when writing to one.
+    #[arg(short = 'w', long)]
+    pub(crate) width: Option<usize>,
+
+    /// What to do with elements that don't fit into --width
+    #[arg(long, value_enum, default_value_t = OverflowOpt::Truncate)]
+    pub(crate) overflow: OverflowOpt,
+
+    /// When to color the tree
+    ///
+    /// With auto, colors are used when writing to a terminal
+    /// and the NO_COLOR environment variable is unset or empty.
+    #[arg(
+        long,
+        name = "WHEN",
+        value_enum,
+        default_value_t = ColorChoice::Auto,
+        help_heading = "Color"
+    )]
+    pub(crate) color: ColorChoice,
+
+    /// Style of lines and branches
+    ///
+    /// Styles are names joined with "+", like "bold+blue", or raw SGR
+    /// parameters, like "38;5;208". Use "none" to leave text unstyled.
+    ///
+    /// Defaults to "bright-black".
+    #[arg(long, name = "STYLE", help_heading = "Color")]
+    pub(crate) color_connector: Option<Style>,
+
+    /// Comma-separated styles of elements, by depth
+    ///
+    /// Deeper elements cycle through the list.
+    /// Defaults to "blue,green,yellow,magenta,cyan".
+    #[arg(long, name = "STYLES", value_delimiter = ',', help_heading = "Color")]
+    pub(crate) color_depths: Option<Vec<Style>>,
+
+    /// Style added to elements without children
+    ///
+    /// Defaults to "none".
+    #[arg(long, name = "LEAF_STYLE", help_heading = "Color")]
+    pub(crate) color_leaf: Option<Style>,
+
+    /// Style added to elements with children
+    ///
+    /// Defaults to "bold".
+    #[arg(long, name = "INTERNAL_STYLE", help_heading = "Color")]
+    pub(crate) color_internal: Option<Style>,
+
+    /// Font family of svg labels
+    #[arg(long, name = "FAMILY", help_heading = "SVG")]
+    pub(crate) svg_font: Option<String>,
+
+    /// Font size of svg labels, in pixels
+    #[arg(long, name = "SIZE", help_heading = "SVG")]
+    pub(crate) svg_font_size: Option<f64>,
+
+    /// Width of a text column in the svg, in pixels
+    ///
+    /// Defaults to 0.6 times the font size.
+    #[arg(long, name = "WIDTH", help_heading = "SVG")]
+    pub(crate) svg_column_width: Option<f64>,
+
+    /// Height of a text row in the svg, in pixels
+    ///
+    /// Defaults to 1.4 times the font size.
+    #[arg(long, name = "HEIGHT", help_heading = "SVG")]
+    pub(crate) svg_row_height: Option<f64>,
+
+    /// Color of svg lines
+    #[arg(long, name = "COLOR", help_heading = "SVG")]
+    pub(crate) svg_stroke: Option<String>,
+
+    /// Width of svg lines, in pixels
+    #[arg(long, name = "STROKE_WIDTH", help_heading = "SVG")]
+    pub(crate) svg_stroke_width: Option<f64>,
+
+    /// Expand html elements up to this depth
+    ///
+    /// Deeper elements start out collapsed.
+    /// Defaults to expanding all of them.
+    #[arg(long, name = "DEPTH", help_heading = "HTML")]
+    pub(crate) open_depth: Option<usize>,
+
+    /// Merge elements sharing a name with a sibling into one
+    ///
+    /// Reading from stdin, all lines are merged into the same trees.
+    #[arg(short = 'M', long)]
+    pub(crate) merge: bool,
+
+    /// Show only the elements at this path, each as its own tree
     ///
+    /// Paths are names separated by "/", starting at the top level.
+    /// Within a name, "*" matches any characters and "?" a single one.
+    /// "**" matches any number of levels. Can be given multiple times.
+    #[arg(long, name = "PATH", value_parser = parse_selector)]
+    pub(crate) select: Vec<String>,
+
+    /// Keep only elements matching this regex, and the elements containing them
+    #[arg(long, name = "PATTERN")]
+    pub(crate) filter: Option<Regex>,
+
+    /// Keep all elements below those matching --filter
+    #[arg(long, requires = "PATTERN")]
+    pub(crate) filter_subtree: bool,
+
+    /// Remove elements matching this regex, and the elements they contain
+    #[arg(long, name = "EXCLUDE_PATTERN")]
+    pub(crate) exclude: Option<Regex>,
+
+    /// Merge elements with a single child into one, joined by this string
+    ///
+    /// Defaults to "/" when given without a value.
+    /// Does nothing with --format-out bracket or json,
+    /// so their output can be read back in unchanged.
+    #[arg(
+        long,
+        name = "JOINER",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "/"
+    )]
+    pub(crate) collapse: Option<String>,
+
+    /// Comma-separated information to append to elements
+    #[arg(long, name = "ANNOTATIONS", value_enum, value_delimiter = ',')]
+    pub(crate) annotate: Vec<AnnotationOpt>,
+
+    /// Hide elements nested deeper than this
+    ///
+    /// Top-level elements are at depth 0.
+    #[arg(long, name = "MAX_DEPTH")]
+    pub(crate) max_depth: Option<usize>,
+
+    /// Element shown in place of hidden children
+    ///
+    /// "{}" is replaced by the number of hidden elements.
+    /// Use an empty string to hide children without a trace.
+    /// Not shown with --format-out bracket or json,
+    /// so their output can be read back in unchanged.
+    #[arg(long, name = "MARKER", default_value = "… ({} more)")]
+    pub(crate) elision: String,
+
+    /// Sort child elements, alphabetically unless given other orders
+    ///
+    /// Orders are separated by commas, like --sort leaves-first,natural,-length.
+    /// Later orders decide between elements the earlier ones consider equal,
+    /// and elements equal by every order keep their input order.
+    /// A leading "-" reverses an order; use --sort=-ORDER when it comes first.
+    /// Orders are: alphabetical, natural, numeric, ci, length, version,
+    /// children, descendants, height and leaves-first.
+    ///
+    /// Without orders, give input before --sort or after "--".
     /// Does nothing when using --sort-by
-    #[arg(short = 'S', long)]
-    pub(crate) sort: bool,
+    #[arg(
+        short = 'S',
+        long,
+        name = "ORDERS",
+        num_args = 0..=1,
+        value_delimiter = ',',
+        value_parser = parse_sort_key,
+        default_missing_value = "alphabetical"
+    )]
+    pub(crate) sort: Vec<SortKeyOpt>,
 
     /// Sort child elements with external program
     #[arg(short = 'B', long, name = "PROGRAM")]
@@ -115,3 +310,124 @@ pub(crate) struct Opt {
     #[arg(short = 'D', long)]
     pub(crate) debug: bool,
 }
+
+fn parse_selector(path: &str) -> Result<String, String> {
+    match path.split('/').all(str::is_empty) {
+        true => Err(String::from("path needs at least one name")),
+        false => Ok(path.to_owned()),
+    }
+}
+
+#[derive(Debug, Clone, Subcommand)]
+pub(crate) enum Command {
+    /// Show the changes from one input to another as a single tree
+    ///
+    /// Elements are matched by name among their siblings.
+    /// Added elements are marked with "+", removed ones with "-".
+    /// Elements moved to another parent are marked with "~",
+    /// followed by where they were moved from.
+    ///
+    /// Options go before the subcommand: txtree --chain diff OLD NEW
+    Diff {
+        /// Input before the changes
+        old: String,
+        /// Input after the changes
+        new: String,
+    },
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum SortOpt {
+    /// By character
+    Alphabetical,
+    /// By character, with numbers compared by value: "item2" before "item10"
+    Natural,
+    /// By the number elements start with
+    Numeric,
+    /// By character, ignoring case
+    Ci,
+    /// Shorter elements first
+    Length,
+    /// By version number: "1.9" before "1.10" before "2.0-rc1" before "2.0"
+    Version,
+    /// Fewest children first
+    Children,
+    /// Fewest elements below first
+    Descendants,
+    /// Fewest levels below first
+    Height,
+    /// Elements without children first
+    LeavesFirst,
+}
+
+/// Order given to --sort, possibly reversed.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct SortKeyOpt {
+    pub(crate) order: SortOpt,
+    pub(crate) descending: bool,
+}
+
+fn parse_sort_key(key: &str) -> Result<SortKeyOpt, String> {
+    let (descending, name) = match key.trim().strip_prefix('-') {
+        Some(name) => (true, name),
+        None => (false, key.trim()),
+    };
+    Ok(SortKeyOpt {
+        order: SortOpt::from_str(name, true)?,
+        descending,
+    })
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum AnnotationOpt {
+    /// Number of children, like "(3)"
+    Children,
+    /// Number of elements below, like "[17]"
+    Descendants,
+    /// Depth, starting at 0 on the top level, like "@2"
+    Depth,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum OverflowOpt {
+    /// Cut off elements, ending them with an ellipsis
+    Truncate,
+    /// Continue elements on the following lines
+    Wrap,
+    /// Leave elements as they are
+    None,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum ColorChoice {
+    /// Color when writing to a terminal, unless NO_COLOR is set
+    Auto,
+    /// Always color
+    Always,
+    /// Never color
+    Never,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub(crate) enum FormatOut {
+    /// Text-based tree graph
+    Tree,
+    /// Same syntax as the input
+    Bracket,
+    /// One JSON object per tree, with "name" and "children"
+    Json,
+    /// Standalone svg document drawn with the tree layout
+    Svg,
+    /// Standalone html document with collapsible elements
+    Html,
+    /// Graphviz digraph, laid out in the direction of --vertical and --invert
+    Dot,
+    /// Mermaid flowchart, laid out in the direction of --vertical and --invert
+    Mermaid,
+    /// PlantUML mind map, growing downwards with --vertical
+    Plantuml,
+    /// LaTeX using the forest package
+    Forest,
+    /// LaTeX using the tikz-qtree package
+    TikzQtree,
+}
@@ -1,21 +1,37 @@
-use std::convert::Infallible;
+use std::{
+    error::Error,
+    fmt::{self, Display},
+};
 
 use clap::builder::{ValueParser, ValueParserFactory};
 use nom::{
     bytes::complete::{tag, take_while},
-    combinator::{map_res, opt},
+    combinator::{map_opt, opt},
     error::ErrorKind,
     multi::separated_list0,
     sequence::{delimited, tuple},
-    FindSubstring, IResult,
+    IResult,
 };
 
 use crate::Node;
 
+/// Makes the following character part of a name.
+pub(crate) const ESCAPE: char = '\\';
+/// Follows [`ESCAPE`] to break a name into lines.
+pub(crate) const NEWLINE: char = 'n';
+
 #[derive(Debug, Clone)]
 pub struct Brackets {
-    open: String,
-    close: String,
+    pub(crate) open: String,
+    pub(crate) close: String,
+}
+impl Default for Brackets {
+    fn default() -> Self {
+        Brackets {
+            open: String::from("["),
+            close: String::from("]"),
+        }
+    }
 }
 impl ValueParserFactory for Brackets {
     type Parser = ValueParser;
@@ -46,9 +62,9 @@ impl ValueParserFactory for Brackets {
 
 #[derive(Debug, Clone)]
 pub struct ParseMode {
-    delimiter: String,
-    brackets: Brackets,
-    trim: bool,
+    pub(crate) delimiter: String,
+    pub(crate) brackets: Brackets,
+    pub(crate) trim: bool,
 }
 impl ParseMode {
     pub fn new(delimiter: String, brackets: Brackets, trim: bool) -> Self {
@@ -58,23 +74,80 @@ impl ParseMode {
             trim,
         }
     }
+
+    fn tags(&self) -> [&str; 3] {
+        [&self.delimiter, &self.brackets.open, &self.brackets.close]
+    }
+
+    /// Whether `input` starts with the delimiter or either bracket.
+    pub(crate) fn tag_at(&self, input: &str) -> bool {
+        self.tags().into_iter().any(|tag| input.starts_with(tag))
+    }
+
+    /// Whether `input` starts with the delimiter or either bracket,
+    /// or ends in the middle of one.
+    pub(crate) fn tag_or_prefix_at(&self, input: &str) -> bool {
+        self.tags()
+            .into_iter()
+            .any(|tag| input.starts_with(tag) || tag.starts_with(input))
+    }
+
+    /// Whether the delimiter or either bracket starts with `c`.
+    fn starts_tag(&self, c: char) -> bool {
+        self.tags().into_iter().any(|tag| tag.starts_with(c))
+    }
+}
+impl Default for ParseMode {
+    fn default() -> Self {
+        ParseMode::new(String::from(","), Brackets::default(), false)
+    }
 }
-impl ToString for ParseMode {
-    fn to_string(&self) -> String {
-        format!(
+impl Display for ParseMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
             "{}{}{}",
             self.delimiter, self.brackets.open, self.brackets.close
         )
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ParseError {
+    /// Parsing stopped before the end of the input, at the contained text
+    Incomplete(String),
+    /// The parser failed outright
+    Failed(String),
+    /// A single root was expected, but this many were found
+    NotSingle(usize),
+}
+impl Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParseError::Incomplete(rest) => write!(f, "Failed to parse input, on {:?}.", rest),
+            ParseError::Failed(e) => write!(f, "Failed to parse input. {}", e),
+            ParseError::NotSingle(n) => write!(f, "Expected a single root, found {}.", n),
+        }
+    }
+}
+impl Error for ParseError {}
+
 pub fn parse_nodes(input: &str, mode: ParseMode) -> IResult<&str, Vec<Node>> {
     node_list(mode)(input)
 }
 
+/// Parses all of `input`, failing if anything is left over.
+pub fn parse_complete(input: &str, mode: ParseMode) -> Result<Vec<Node>, ParseError> {
+    match parse_nodes(input, mode) {
+        Ok(("", nodes)) => Ok(nodes),
+        Ok((rest, _)) => Err(ParseError::Incomplete(rest.to_owned())),
+        Err(e) => Err(ParseError::Failed(format!("{:?}", e))),
+    }
+}
+
 fn single_node(mode: ParseMode) -> impl Fn(&str) -> IResult<&str, Node> {
     move |i: &str| {
-        map_res(
+        map_opt(
             tuple((node_name(mode.clone()), opt(bracketed(mode.clone())))),
             map_node(mode.clone()),
         )(i)
@@ -83,15 +156,17 @@ fn single_node(mode: ParseMode) -> impl Fn(&str) -> IResult<&str, Node> {
 
 pub fn node_name(mode: ParseMode) -> impl Fn(&str) -> IResult<&str, &str> {
     move |i: &str| {
-        let max = i.len();
-        let index = [
-            i.find_substring(&mode.delimiter).unwrap_or(max),
-            i.find_substring(&mode.brackets.open).unwrap_or(max),
-            i.find_substring(&mode.brackets.close).unwrap_or(max),
-        ]
-        .into_iter()
-        .min()
-        .unwrap();
+        let mut chars = i.char_indices();
+        let mut index = i.len();
+        while let Some((pos, c)) = chars.next() {
+            if mode.tag_at(&i[pos..]) {
+                index = pos;
+                break;
+            }
+            if c == ESCAPE {
+                chars.next();
+            }
+        }
 
         if index > 0 {
             Ok((&i[index..], &i[..index]))
@@ -133,14 +208,40 @@ fn wtag(wtag: String) -> impl Fn(&str) -> IResult<&str, (Option<&str>, &str)> {
     }
 }
 
-fn map_node(mode: ParseMode) -> impl Fn((&str, Option<Vec<Node>>)) -> Result<Node, Infallible> {
+/// Builds a node, failing on names left empty by trimming.
+fn map_node(mode: ParseMode) -> impl Fn((&str, Option<Vec<Node>>)) -> Option<Node> {
     move |(name, children)| {
-        Ok(Node::new(
-            match mode.trim {
-                true => name.trim().to_owned(),
-                false => name.to_owned(),
-            },
-            children.unwrap_or_default(),
-        ))
+        let name = unescape(name, &mode);
+        (!name.is_empty()).then(|| Node::new(name, children.unwrap_or_default()))
+    }
+}
+
+/// Resolves escaped characters, trimming only whitespace that isn't escaped.
+///
+/// A backslash escapes the first character of delimiters and brackets,
+/// whitespace and itself, and followed by `n` becomes a line break.
+/// Any other backslash is kept.
+fn unescape(name: &str, mode: &ParseMode) -> String {
+    let mut chars = name.char_indices();
+    let mut resolved = Vec::new();
+    while let Some((pos, c)) = chars.next() {
+        let rest = &name[pos + c.len_utf8()..];
+        let escapes = c == ESCAPE
+            && rest.starts_with(|next: char| {
+                next == ESCAPE || next == NEWLINE || next.is_whitespace() || mode.starts_tag(next)
+            });
+        resolved.push(match escapes.then(|| chars.next()).flatten() {
+            Some((_, NEWLINE)) => ('\n', true),
+            Some((_, next)) => (next, true),
+            None => (c, false),
+        });
+    }
+
+    let trimmable = |&(c, escaped): &(char, bool)| mode.trim && !escaped && c.is_whitespace();
+    let start = resolved.iter().position(|c| !trimmable(c));
+    let end = resolved.iter().rposition(|c| !trimmable(c));
+    match (start, end) {
+        (Some(start), Some(end)) => resolved[start..=end].iter().map(|&(c, _)| c).collect(),
+        _ => String::new(),
     }
 }
@@ -1,12 +1,25 @@
 mod alignment;
+mod canvas;
+mod color;
+mod fit;
+mod horizontal;
+mod svg;
 mod symbols;
+mod vertical;
 
-use std::fmt::Debug;
+use std::{
+    fmt::{self, Debug},
+    io,
+};
 
 pub use self::{
     alignment::{Alignment, AlignmentBuilder},
+    color::{Colors, ColorsBuilder, Style},
+    fit::Overflow,
+    svg::{SvgStyle, SvgStyleBuilder},
     symbols::{Symbols, SymbolsBuilder},
 };
+use crate::Node;
 
 #[derive(Debug, Clone)]
 pub struct RenderMode {
@@ -19,6 +32,8 @@ pub struct RenderMode {
     vertical: bool,
     invert: bool,
     align_root: Alignment,
+    colors: Option<Colors>,
+    fit: Option<fit::Fit>,
 }
 impl RenderMode {
     pub fn new(
@@ -39,10 +54,130 @@ impl RenderMode {
             vertical,
             invert,
             align_root,
+            colors: None,
+            fit: None,
+        }
+    }
+
+    /// Styles text output with ANSI escape sequences. `None` keeps it monochrome.
+    pub fn with_colors(mut self, colors: Option<Colors>) -> Self {
+        self.colors = colors;
+        self
+    }
+
+    /// Fits labels into `width` columns, after the lines and branches left of them.
+    /// `None` leaves labels as they are.
+    ///
+    /// Vertical trees fit every label into the whole width.
+    pub fn with_width(mut self, width: Option<usize>, overflow: Overflow) -> Self {
+        self.fit = width.map(|width| fit::Fit { width, overflow });
+        self
+    }
+
+    /// Writes `node` and its descendants to `out`, ending every row with a newline.
+    pub fn render<W: fmt::Write>(&self, node: &Node, out: &mut W) -> fmt::Result {
+        self.layout(
+            node,
+            canvas::TextCanvas::new(out, &self.symbol_set, self.colors.as_ref()),
+        )
+    }
+
+    /// Like [`RenderMode::render`], but for byte streams.
+    ///
+    /// Rows are written as they are laid out, so wrap `out` in a
+    /// [`io::BufWriter`] when it is unbuffered.
+    pub fn render_to<W: io::Write>(&self, node: &Node, out: &mut W) -> io::Result<()> {
+        write_io(out, |out| self.render(node, out))
+    }
+
+    /// Draws `nodes` one below the other as a standalone svg document.
+    ///
+    /// The drawing follows the same layout as [`RenderMode::render`],
+    /// with box-drawing symbols replaced by lines.
+    pub fn render_svg<W: fmt::Write>(
+        &self,
+        nodes: &[Node],
+        style: &SvgStyle,
+        out: &mut W,
+    ) -> fmt::Result {
+        let mut measure = canvas::Measure::new(&self.symbol_set);
+        for node in nodes {
+            self.layout(node, &mut measure)?;
+        }
+        let mut svg = svg::SvgCanvas::new(out, &self.symbol_set, style);
+        svg.begin(measure.columns, measure.rows)?;
+        for node in nodes {
+            self.layout(node, &mut svg)?;
+        }
+        svg.end()
+    }
+
+    /// Like [`RenderMode::render_svg`], but for byte streams.
+    pub fn render_svg_to<W: io::Write>(
+        &self,
+        nodes: &[Node],
+        style: &SvgStyle,
+        out: &mut W,
+    ) -> io::Result<()> {
+        write_io(out, |out| self.render_svg(nodes, style, out))
+    }
+
+    fn layout<C: canvas::Canvas>(&self, node: &Node, canvas: C) -> fmt::Result {
+        let fitted = self.fit.map(|fit| match self.vertical {
+            true => fit.tree(node, 0, &|_, _| 0),
+            false => fit.tree(node, 0, &|node, column| {
+                horizontal::child_column(self, node, column)
+            }),
+        });
+        let node = fitted.as_ref().unwrap_or(node);
+        match self.vertical {
+            true => vertical::layout(self, node, canvas),
+            false => horizontal::layout(self, node, canvas),
         }
     }
 }
 
+impl Default for RenderMode {
+    fn default() -> Self {
+        RenderMode::new(
+            Symbols::default(),
+            0,
+            0,
+            false,
+            false,
+            false,
+            AlignmentBuilder::new().build(),
+        )
+    }
+}
+
+fn write_io<W: io::Write>(
+    out: &mut W,
+    write: impl FnOnce(&mut IoAdapter<'_, W>) -> fmt::Result,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter { out, error: None };
+    match write(&mut adapter) {
+        Ok(()) => Ok(()),
+        Err(fmt::Error) => Err(adapter
+            .error
+            .unwrap_or_else(|| io::Error::other("Failed to render tree"))),
+    }
+}
+
+/// Forwards text to an [`io::Write`], keeping the error that stopped it.
+struct IoAdapter<'w, W> {
+    out: &'w mut W,
+    error: Option<io::Error>,
+}
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.out.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
 #[derive(Clone)]
 struct DemoGrid(String);
 impl DemoGrid {
@@ -58,8 +193,14 @@ impl DemoGrid {
             sym.cap_left.as_str()
         };
 
-        let horizontal_a = sym.horizontal.to_string().repeat(cap_right.chars().count());
-        let horizontal_b = sym.horizontal.to_string().repeat(cap_left.chars().count());
+        let horizontal_a = sym
+            .horizontal
+            .to_string()
+            .repeat(canvas::text_width(cap_right));
+        let horizontal_b = sym
+            .horizontal
+            .to_string()
+            .repeat(canvas::text_width(cap_left));
 
         DemoGrid(format!(
             "{i}{b}{g}{c}{j}\n\
@@ -0,0 +1,228 @@
+use std::fmt::{self, Write};
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+use super::{
+    color::{Colors, Painter},
+    Symbols,
+};
+
+/// Directions a box-drawing glyph connects to.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub(crate) struct Joint {
+    pub(crate) up: bool,
+    pub(crate) down: bool,
+    pub(crate) left: bool,
+    pub(crate) right: bool,
+}
+impl Joint {
+    pub(crate) const VERTICAL: Joint = Joint {
+        up: true,
+        down: true,
+        left: false,
+        right: false,
+    };
+
+    pub(crate) fn mirrored(self) -> Self {
+        Joint {
+            left: self.right,
+            right: self.left,
+            ..self
+        }
+    }
+    pub(crate) fn flipped(self) -> Self {
+        Joint {
+            up: self.down,
+            down: self.up,
+            ..self
+        }
+    }
+
+    pub(crate) fn symbol(&self, sym: &Symbols) -> char {
+        match (self.up, self.down, self.left, self.right) {
+            (true, true, true, true) => sym.crossing,
+            (true, true, false, true) => sym.branch_right,
+            (true, true, true, false) => sym.branch_left,
+            (false, true, true, true) => sym.branch_down,
+            (true, false, true, true) => sym.branch_up,
+            (false, true, false, true) => sym.corner_down_right,
+            (false, true, true, false) => sym.corner_down_left,
+            (true, false, false, true) => sym.corner_up_right,
+            (true, false, true, false) => sym.corner_up_left,
+            (_, _, false, false) => sym.vertical,
+            (false, false, _, _) => sym.horizontal,
+        }
+    }
+}
+
+/// Short line between a joint and a label.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Cap {
+    /// Sits right of a joint, left of a label
+    Right,
+    /// Sits left of a joint, right of a label
+    Left,
+}
+impl Cap {
+    pub(crate) fn mirrored(self) -> Self {
+        match self {
+            Cap::Right => Cap::Left,
+            Cap::Left => Cap::Right,
+        }
+    }
+
+    pub(crate) fn symbol(self, sym: &Symbols) -> &str {
+        match self {
+            Cap::Right => &sym.cap_right,
+            Cap::Left => &sym.cap_left,
+        }
+    }
+}
+
+/// Piece of a rendered row, from left to right.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum Cell<'a> {
+    Space(usize),
+    Joint(Joint),
+    Line(usize),
+    Cap(Cap),
+    /// Name of a node, at `depth` below the root
+    Label {
+        text: &'a str,
+        depth: usize,
+        leaf: bool,
+    },
+}
+impl Cell<'_> {
+    pub(crate) fn width(&self, sym: &Symbols) -> usize {
+        match self {
+            Cell::Space(n) | Cell::Line(n) => *n,
+            Cell::Joint(_) => 1,
+            Cell::Cap(cap) => text_width(cap.symbol(sym)),
+            Cell::Label { text, .. } => text_width(text),
+        }
+    }
+
+    pub(crate) fn mirrored(self) -> Self {
+        match self {
+            Cell::Joint(joint) => Cell::Joint(joint.mirrored()),
+            Cell::Cap(cap) => Cell::Cap(cap.mirrored()),
+            other => other,
+        }
+    }
+    pub(crate) fn flipped(self) -> Self {
+        match self {
+            Cell::Joint(joint) => Cell::Joint(joint.flipped()),
+            other => other,
+        }
+    }
+}
+
+/// Number of columns a string takes up in a terminal.
+///
+/// Widths are summed per grapheme cluster, so combining marks and emoji
+/// sequences count once, and wide East Asian characters count twice.
+pub(crate) fn text_width(text: &str) -> usize {
+    text.graphemes(true).map(|g| g.width()).sum()
+}
+
+/// Lines of a node name, drawn one below the other.
+pub(crate) fn label_lines(name: &str) -> impl Iterator<Item = &str> {
+    name.split('\n')
+}
+
+/// Number of columns the widest line of a node name takes up.
+pub(crate) fn label_width(name: &str) -> usize {
+    label_lines(name).map(text_width).max().unwrap_or(0)
+}
+
+/// Receives the rows produced by a layout, one at a time.
+pub(crate) trait Canvas {
+    fn row(&mut self, cells: &[Cell]) -> fmt::Result;
+}
+
+impl<C: Canvas> Canvas for &mut C {
+    fn row(&mut self, cells: &[Cell]) -> fmt::Result {
+        (**self).row(cells)
+    }
+}
+
+/// Ignores all rows. Used to measure a layout before drawing it.
+pub(crate) struct Discard;
+impl Canvas for Discard {
+    fn row(&mut self, _: &[Cell]) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// Counts rows and the columns of the widest one.
+pub(crate) struct Measure<'s> {
+    symbols: &'s Symbols,
+    pub(crate) rows: usize,
+    pub(crate) columns: usize,
+}
+impl<'s> Measure<'s> {
+    pub(crate) fn new(symbols: &'s Symbols) -> Self {
+        Measure {
+            symbols,
+            rows: 0,
+            columns: 0,
+        }
+    }
+}
+impl Canvas for Measure<'_> {
+    fn row(&mut self, cells: &[Cell]) -> fmt::Result {
+        let width = cells.iter().map(|c| c.width(self.symbols)).sum();
+        self.rows += 1;
+        self.columns = self.columns.max(width);
+        Ok(())
+    }
+}
+
+/// Writes rows as text, terminating each with a newline.
+///
+/// With colors, every row ends with its styles reset.
+pub(crate) struct TextCanvas<'w, W> {
+    out: &'w mut W,
+    symbols: &'w Symbols,
+    colors: Option<&'w Colors>,
+    painter: Painter,
+}
+impl<'w, W: Write> TextCanvas<'w, W> {
+    pub(crate) fn new(out: &'w mut W, symbols: &'w Symbols, colors: Option<&'w Colors>) -> Self {
+        TextCanvas {
+            out,
+            symbols,
+            colors,
+            painter: Painter::new(),
+        }
+    }
+
+    fn repeat(&mut self, c: char, n: usize) -> fmt::Result {
+        (0..n).try_for_each(|_| self.out.write_char(c))
+    }
+}
+impl<W: Write> Canvas for TextCanvas<'_, W> {
+    fn row(&mut self, cells: &[Cell]) -> fmt::Result {
+        for cell in cells {
+            if let Some(colors) = self.colors {
+                let style = match cell {
+                    Cell::Space(_) => None,
+                    Cell::Joint(_) | Cell::Line(_) | Cell::Cap(_) => Some(colors.connector.clone()),
+                    Cell::Label { depth, leaf, .. } => Some(colors.label(*depth, *leaf)),
+                };
+                self.painter.paint(self.out, style)?;
+            }
+            match cell {
+                Cell::Space(n) => self.repeat(' ', *n)?,
+                Cell::Line(n) => self.repeat(self.symbols.horizontal, *n)?,
+                Cell::Joint(joint) => self.out.write_char(joint.symbol(self.symbols))?,
+                Cell::Cap(cap) => self.out.write_str(cap.symbol(self.symbols))?,
+                Cell::Label { text, .. } => self.out.write_str(text)?,
+            }
+        }
+        self.painter.paint(self.out, None)?;
+        self.out.write_char('\n')
+    }
+}
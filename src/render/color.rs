@@ -0,0 +1,175 @@
+use std::fmt::{self, Display, Write};
+
+use clap::builder::{ValueParser, ValueParserFactory};
+
+/// Terminal text attributes, as the parameters of an ANSI SGR sequence.
+///
+/// Parsed from names joined with `+`, like `bold+blue`, or from raw
+/// parameters like `38;5;208`. `none` leaves text unstyled.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Style(String);
+impl Style {
+    /// Parameters for a single attribute name.
+    fn code(name: &str) -> Option<&'static str> {
+        Some(match name {
+            "none" => "",
+            "bold" => "1",
+            "dim" => "2",
+            "italic" => "3",
+            "underline" => "4",
+            "reverse" => "7",
+            "black" => "30",
+            "red" => "31",
+            "green" => "32",
+            "yellow" => "33",
+            "blue" => "34",
+            "magenta" => "35",
+            "cyan" => "36",
+            "white" => "37",
+            "bright-black" => "90",
+            "bright-red" => "91",
+            "bright-green" => "92",
+            "bright-yellow" => "93",
+            "bright-blue" => "94",
+            "bright-magenta" => "95",
+            "bright-cyan" => "96",
+            "bright-white" => "97",
+            _ => return None,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    fn join(&mut self, params: &str) {
+        if !self.0.is_empty() && !params.is_empty() {
+            self.0.push(';');
+        }
+        self.0.push_str(params);
+    }
+}
+impl ValueParserFactory for Style {
+    type Parser = ValueParser;
+
+    fn value_parser() -> Self::Parser {
+        ValueParser::new(|string: &str| -> Result<Self, String> {
+            let mut style = Style::default();
+            for part in string.split('+').map(str::trim) {
+                let raw = !part.is_empty() && part.chars().all(|c| c.is_ascii_digit() || c == ';');
+                match Style::code(&part.to_ascii_lowercase()) {
+                    Some(code) => style.join(code),
+                    None if raw => style.join(part),
+                    None => return Err(format!("Unknown style {:?}", part)),
+                }
+            }
+            Ok(style)
+        })
+    }
+}
+impl Display for Style {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Styles applied to the text output of a tree.
+///
+/// Labels take the palette entry for their depth, cycling through the
+/// palette, combined with the style for leaves or internal nodes.
+#[derive(Debug, Clone)]
+pub struct Colors {
+    pub(super) connector: Style,
+    pub(super) depths: Vec<Style>,
+    pub(super) leaf: Style,
+    pub(super) internal: Style,
+}
+impl Colors {
+    /// Style of a label at `depth`.
+    pub(super) fn label(&self, depth: usize, leaf: bool) -> Style {
+        let mut style = match self.depths.is_empty() {
+            true => Style::default(),
+            false => self.depths[depth % self.depths.len()].clone(),
+        };
+        style.join(match leaf {
+            true => &self.leaf.0,
+            false => &self.internal.0,
+        });
+        style
+    }
+}
+impl Default for Colors {
+    fn default() -> Self {
+        ColorsBuilder::new().build()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ColorsBuilder {
+    connector: Option<Style>,
+    depths: Option<Vec<Style>>,
+    leaf: Option<Style>,
+    internal: Option<Style>,
+}
+impl ColorsBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn connector(&mut self, connector: Option<Style>) -> &mut Self {
+        self.connector = connector;
+        self
+    }
+    pub fn depths(&mut self, depths: Option<Vec<Style>>) -> &mut Self {
+        self.depths = depths;
+        self
+    }
+    pub fn leaf(&mut self, leaf: Option<Style>) -> &mut Self {
+        self.leaf = leaf;
+        self
+    }
+    pub fn internal(&mut self, internal: Option<Style>) -> &mut Self {
+        self.internal = internal;
+        self
+    }
+
+    pub fn build(&self) -> Colors {
+        let style = |params: &str| Style(String::from(params));
+        Colors {
+            connector: self.connector.clone().unwrap_or_else(|| style("90")),
+            depths: self.depths.clone().unwrap_or_else(|| {
+                ["34", "32", "33", "35", "36"]
+                    .into_iter()
+                    .map(style)
+                    .collect()
+            }),
+            leaf: self.leaf.clone().unwrap_or_default(),
+            internal: self.internal.clone().unwrap_or_else(|| style("1")),
+        }
+    }
+}
+
+/// Writes escape sequences, switching styles only when they change.
+pub(super) struct Painter {
+    active: Option<Style>,
+}
+impl Painter {
+    pub(super) fn new() -> Self {
+        Painter { active: None }
+    }
+
+    pub(super) fn paint<W: Write>(&mut self, out: &mut W, style: Option<Style>) -> fmt::Result {
+        let style = style.filter(|s| !s.is_empty());
+        if style == self.active {
+            return Ok(());
+        }
+        if self.active.is_some() {
+            out.write_str("\x1b[0m")?;
+        }
+        if let Some(style) = &style {
+            write!(out, "\x1b[{}m", style)?;
+        }
+        self.active = style;
+        Ok(())
+    }
+}
@@ -0,0 +1,104 @@
+use unicode_segmentation::UnicodeSegmentation;
+
+use super::canvas::{label_lines, text_width};
+use crate::Node;
+
+const ELLIPSIS: &str = "…";
+
+/// How labels are shortened when they exceed the output width.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Overflow {
+    /// Cut off lines, ending them with an ellipsis
+    #[default]
+    Truncate,
+    /// Continue lines below, breaking between words where possible
+    Wrap,
+}
+
+/// Output width and what to do with labels that don't fit.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct Fit {
+    pub(super) width: usize,
+    pub(super) overflow: Overflow,
+}
+impl Fit {
+    /// Copies `node` with every label fitted into the columns left of it.
+    ///
+    /// `child_column` gives the column the children of a fitted node start at.
+    pub(super) fn tree(
+        &self,
+        node: &Node,
+        column: usize,
+        child_column: &impl Fn(&Node, usize) -> usize,
+    ) -> Node {
+        let mut fitted = Node::new(self.label(&node.name, column), Vec::new());
+        let next = child_column(&fitted, column);
+        fitted.children = node
+            .children
+            .iter()
+            .map(|child| self.tree(child, next, child_column))
+            .collect();
+        fitted
+    }
+
+    fn label(&self, name: &str, column: usize) -> String {
+        let budget = self.width.saturating_sub(column).max(1);
+        let mut fitted = String::with_capacity(name.len());
+        for (index, line) in label_lines(name).enumerate() {
+            if index > 0 {
+                fitted.push('\n');
+            }
+            match self.overflow {
+                Overflow::Truncate => truncate(&mut fitted, line, budget),
+                Overflow::Wrap => wrap(&mut fitted, line, budget),
+            }
+        }
+        fitted
+    }
+}
+
+fn truncate(out: &mut String, line: &str, budget: usize) {
+    if text_width(line) <= budget {
+        out.push_str(line);
+        return;
+    }
+    let budget = budget - text_width(ELLIPSIS);
+    let mut width = 0;
+    for grapheme in line.graphemes(true) {
+        width += text_width(grapheme);
+        if width > budget {
+            break;
+        }
+        out.push_str(grapheme);
+    }
+    out.push_str(ELLIPSIS);
+}
+
+/// Breaks `line` after the last whitespace that keeps it within `budget`,
+/// or after the last grapheme that does when there is none.
+fn wrap(out: &mut String, line: &str, budget: usize) {
+    let mut rest = line;
+    while text_width(rest.trim_end()) > budget {
+        let mut width = 0;
+        let mut end = 0;
+        let mut space = None;
+        for (pos, grapheme) in rest.grapheme_indices(true) {
+            if grapheme.trim().is_empty() {
+                space = Some(pos);
+            }
+            width += text_width(grapheme);
+            if width > budget && end > 0 {
+                break;
+            }
+            end = pos + grapheme.len();
+        }
+        let (head, tail) = match space {
+            Some(pos) if pos > 0 => (&rest[..pos], rest[pos..].trim_start()),
+            _ => (&rest[..end], &rest[end..]),
+        };
+        out.push_str(head.trim_end());
+        out.push('\n');
+        rest = tail;
+    }
+    out.push_str(rest);
+}
@@ -0,0 +1,308 @@
+use std::fmt;
+
+use super::{
+    canvas::{label_lines, label_width, text_width, Canvas, Cap, Cell, Discard, Joint},
+    Alignment, RenderMode,
+};
+use crate::Node;
+
+pub(super) fn layout<C: Canvas>(mode: &RenderMode, node: &Node, canvas: C) -> fmt::Result {
+    let mirror_width = match mode.invert {
+        true => {
+            let mut measure = Horizontal::new(mode, Discard, None);
+            measure.owner(node, None)?;
+            Some(measure.max_width)
+        }
+        false => None,
+    };
+    Horizontal::new(mode, canvas, mirror_width).owner(node, None)
+}
+
+/// Columns between the start of a node and the joints towards its children.
+fn indent(mode: &RenderMode, node: &Node) -> usize {
+    match mode.chain {
+        true => label_width(&node.name) + cap_width(mode, Cap::Left) + mode.gaps as usize,
+        false => 0,
+    }
+}
+
+/// Column the children of `node` start at, when `node` starts at `column`.
+pub(super) fn child_column(mode: &RenderMode, node: &Node, column: usize) -> usize {
+    column + indent(mode, node) + 1 + mode.gaps as usize + cap_width(mode, Cap::Right)
+}
+
+fn cap_width(mode: &RenderMode, cap: Cap) -> usize {
+    text_width(cap.symbol(&mode.symbol_set))
+}
+
+/// Column holding the joints towards the children of `node`.
+struct Level<'a> {
+    node: &'a Node,
+    indent: usize,
+    line: bool,
+}
+
+/// Children of a node, grouped by where they are drawn relative to it.
+struct Split<'a> {
+    above: &'a [Node],
+    inline: Option<&'a Node>,
+    below: &'a [Node],
+}
+
+struct Horizontal<'a, C> {
+    mode: &'a RenderMode,
+    canvas: C,
+    mirror_width: Option<usize>,
+    max_width: usize,
+    started: bool,
+    levels: Vec<Level<'a>>,
+    row: Vec<Cell<'a>>,
+    mirrored: Vec<Cell<'a>>,
+}
+impl<'a, C: Canvas> Horizontal<'a, C> {
+    fn new(mode: &'a RenderMode, canvas: C, mirror_width: Option<usize>) -> Self {
+        Horizontal {
+            mode,
+            canvas,
+            mirror_width,
+            max_width: 0,
+            started: false,
+            levels: Vec::new(),
+            row: Vec::new(),
+            mirrored: Vec::new(),
+        }
+    }
+
+    /// Draws the row starting with `node`, along with everything above and below it.
+    ///
+    /// When chaining, the row continues with the inline child of `node`,
+    /// its inline child and so on. Each node on the row pushes a level.
+    fn owner(&mut self, node: &'a Node, connector: Option<Joint>) -> fmt::Result {
+        let base = self.levels.len();
+
+        let mut current = Some(node);
+        while let Some(node) = current {
+            let split = self.split(node);
+            self.levels.push(Level {
+                node,
+                indent: self.indent(node),
+                line: false,
+            });
+            for (index, child) in split.above.iter().enumerate() {
+                let joint = Joint {
+                    up: index > 0,
+                    down: true,
+                    left: false,
+                    right: true,
+                };
+                self.child(child, joint)?;
+            }
+            self.top().line = !split.above.is_empty();
+            current = split.inline;
+        }
+
+        self.gap_rows()?;
+        self.row.clear();
+        if let Some(joint) = connector {
+            self.prefix(base - 1);
+            self.connector(base - 1, joint);
+        }
+        self.labels(base, 0);
+        self.flush()?;
+
+        if let Some(joint) = connector {
+            self.levels[base - 1].line = joint.down;
+        }
+        let height = self.levels[base..]
+            .iter()
+            .map(|level| label_lines(&level.node.name).count())
+            .max()
+            .unwrap_or(1);
+        for line in 1..height {
+            self.row.clear();
+            self.prefix(base);
+            self.labels(base, line);
+            self.flush()?;
+        }
+
+        for index in base..self.levels.len() {
+            let below = self.split(self.levels[index].node).below;
+            self.levels[index].line = !below.is_empty();
+        }
+
+        while self.levels.len() > base {
+            let node = self.top().node;
+            let below = self.split(node).below;
+            let last = below.len().saturating_sub(1);
+            for (index, child) in below.iter().enumerate() {
+                let joint = Joint {
+                    up: true,
+                    down: index < last,
+                    left: false,
+                    right: true,
+                };
+                self.child(child, joint)?;
+            }
+            self.levels.pop();
+        }
+        Ok(())
+    }
+
+    /// Pushes one line of the labels of the nodes on the row, from level `base` on.
+    ///
+    /// Shorter labels are padded to keep the junctions of a chain lined up.
+    /// Below the first line, junctions continue as lines towards the children below.
+    fn labels(&mut self, base: usize, line: usize) {
+        for index in base..self.levels.len() {
+            let node = self.levels[index].node;
+            let text = label_lines(&node.name).nth(line).unwrap_or_default();
+            self.push(Cell::Label {
+                text,
+                depth: index,
+                leaf: node.children.is_empty(),
+            });
+            self.push(Cell::Space(label_width(&node.name) - text_width(text)));
+            if !self.mode.chain || node.children.is_empty() {
+                continue;
+            }
+            match line {
+                0 => self.junction(node),
+                _ => {
+                    let split = self.split(node);
+                    let gaps = self.mode.gaps as usize;
+                    self.push(Cell::Space(self.cap_width(Cap::Left) + gaps));
+                    self.push(match split.below.is_empty() {
+                        true => Cell::Space(1),
+                        false => Cell::Joint(Joint::VERTICAL),
+                    });
+                    if split.inline.is_some() {
+                        self.push(Cell::Space(gaps + self.cap_width(Cap::Right)));
+                    }
+                }
+            }
+        }
+    }
+
+    fn child(&mut self, child: &'a Node, joint: Joint) -> fmt::Result {
+        self.top().line = joint.up;
+        self.owner(child, Some(joint))
+    }
+
+    fn split(&self, node: &'a Node) -> Split<'a> {
+        let children = node.children.as_slice();
+        let len = children.len();
+        let half = len / 2;
+        let (above, inline, below): (&[Node], _, &[Node]) =
+            match (&self.mode.align_root, self.mode.chain && len > 0) {
+                (Alignment::Start, false) => (&[], None, children),
+                (Alignment::End, false) => (children, None, &[]),
+                (Alignment::Middle, false) => (&children[..half], None, &children[half..]),
+                (Alignment::Start, true) => (&[], children.first(), &children[1..]),
+                (Alignment::End, true) => (&children[..len - 1], children.last(), &[]),
+                (Alignment::Middle, true) if len % 2 == 1 => (
+                    &children[..half],
+                    Some(&children[half]),
+                    &children[half + 1..],
+                ),
+                (Alignment::Middle, true) => (&children[..half], None, &children[half..]),
+            };
+        Split {
+            above,
+            inline,
+            below,
+        }
+    }
+
+    fn indent(&self, node: &Node) -> usize {
+        indent(self.mode, node)
+    }
+
+    fn cap_width(&self, cap: Cap) -> usize {
+        cap_width(self.mode, cap)
+    }
+
+    fn top(&mut self) -> &mut Level<'a> {
+        self.levels.last_mut().expect("Layout should have a level")
+    }
+
+    fn push(&mut self, cell: Cell<'a>) {
+        match cell {
+            Cell::Space(0) | Cell::Line(0) | Cell::Label { text: "", .. } => (),
+            cell => self.row.push(cell),
+        }
+    }
+
+    fn prefix(&mut self, depth: usize) {
+        let tail = self.mode.gaps as usize + self.cap_width(Cap::Right);
+        for index in 0..depth {
+            let Level { indent, line, .. } = self.levels[index];
+            self.push(Cell::Space(indent));
+            self.push(match line {
+                true => Cell::Joint(Joint::VERTICAL),
+                false => Cell::Space(1),
+            });
+            self.push(Cell::Space(tail));
+        }
+    }
+
+    fn connector(&mut self, depth: usize, joint: Joint) {
+        self.push(Cell::Space(self.levels[depth].indent));
+        self.push(Cell::Joint(joint));
+        self.push(Cell::Line(self.mode.gaps as usize));
+        self.push(Cell::Cap(Cap::Right));
+    }
+
+    fn junction(&mut self, node: &'a Node) {
+        let split = self.split(node);
+        self.push(Cell::Cap(Cap::Left));
+        self.push(Cell::Line(self.mode.gaps as usize));
+        self.push(Cell::Joint(Joint {
+            up: !split.above.is_empty(),
+            down: !split.below.is_empty(),
+            left: true,
+            right: split.inline.is_some(),
+        }));
+        if split.inline.is_some() {
+            self.push(Cell::Line(self.mode.gaps as usize));
+            self.push(Cell::Cap(Cap::Right));
+        }
+    }
+
+    fn gap_rows(&mut self) -> fmt::Result {
+        if !self.started {
+            self.started = true;
+            return Ok(());
+        }
+        for _ in 0..self.mode.lines {
+            self.row.clear();
+            self.prefix(self.levels.len());
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> fmt::Result {
+        while let Some(Cell::Space(_)) = self.row.last() {
+            self.row.pop();
+        }
+        let symbols = &self.mode.symbol_set;
+        let width = self.row.iter().map(|c| c.width(symbols)).sum();
+        self.max_width = self.max_width.max(width);
+
+        let Some(total) = self.mirror_width else {
+            return self.canvas.row(&self.row);
+        };
+        let start = self
+            .row
+            .iter()
+            .position(|c| !matches!(c, Cell::Space(_)))
+            .unwrap_or(self.row.len());
+        self.mirrored.clear();
+        if total > width {
+            self.mirrored.push(Cell::Space(total - width));
+        }
+        self.mirrored
+            .extend(self.row[start..].iter().rev().map(|c| c.mirrored()));
+        self.canvas.row(&self.mirrored)
+    }
+}
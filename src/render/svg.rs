@@ -0,0 +1,242 @@
+use std::fmt::{self, Write};
+
+use super::{
+    canvas::{text_width, Canvas, Cap, Cell},
+    Symbols,
+};
+use crate::format::escape_xml;
+
+/// Appearance of trees drawn with [`RenderMode::render_svg`](super::RenderMode::render_svg).
+///
+/// Lengths are in pixels. Every column and row of the text layout
+/// becomes a cell of `column_width` by `row_height`.
+#[derive(Debug, Clone)]
+pub struct SvgStyle {
+    pub(super) font_family: String,
+    pub(super) font_size: f64,
+    pub(super) column_width: f64,
+    pub(super) row_height: f64,
+    pub(super) padding: f64,
+    pub(super) text_color: String,
+    pub(super) stroke: String,
+    pub(super) stroke_width: f64,
+}
+impl Default for SvgStyle {
+    fn default() -> Self {
+        SvgStyleBuilder::new().build()
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct SvgStyleBuilder {
+    font_family: Option<String>,
+    font_size: Option<f64>,
+    column_width: Option<f64>,
+    row_height: Option<f64>,
+    padding: Option<f64>,
+    text_color: Option<String>,
+    stroke: Option<String>,
+    stroke_width: Option<f64>,
+}
+impl SvgStyleBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn font_family(&mut self, font_family: Option<String>) -> &mut Self {
+        self.font_family = font_family;
+        self
+    }
+    pub fn font_size(&mut self, font_size: Option<f64>) -> &mut Self {
+        self.font_size = font_size;
+        self
+    }
+    pub fn column_width(&mut self, column_width: Option<f64>) -> &mut Self {
+        self.column_width = column_width;
+        self
+    }
+    pub fn row_height(&mut self, row_height: Option<f64>) -> &mut Self {
+        self.row_height = row_height;
+        self
+    }
+    pub fn padding(&mut self, padding: Option<f64>) -> &mut Self {
+        self.padding = padding;
+        self
+    }
+    pub fn text_color(&mut self, text_color: Option<String>) -> &mut Self {
+        self.text_color = text_color;
+        self
+    }
+    pub fn stroke(&mut self, stroke: Option<String>) -> &mut Self {
+        self.stroke = stroke;
+        self
+    }
+    pub fn stroke_width(&mut self, stroke_width: Option<f64>) -> &mut Self {
+        self.stroke_width = stroke_width;
+        self
+    }
+
+    /// Builds the style. Spacing left unset is derived from the font size.
+    pub fn build(&self) -> SvgStyle {
+        let font_size = self.font_size.unwrap_or(14.0);
+        let column_width = self.column_width.unwrap_or(font_size * 0.6);
+        SvgStyle {
+            font_family: self
+                .font_family
+                .clone()
+                .unwrap_or_else(|| String::from("monospace")),
+            font_size,
+            column_width,
+            row_height: self.row_height.unwrap_or(font_size * 1.4),
+            padding: self.padding.unwrap_or(column_width),
+            text_color: self
+                .text_color
+                .clone()
+                .unwrap_or_else(|| String::from("black")),
+            stroke: self.stroke.clone().unwrap_or_else(|| String::from("black")),
+            stroke_width: self.stroke_width.unwrap_or(1.0),
+        }
+    }
+}
+
+/// Draws rows as lines and text elements of a standalone svg document.
+pub(super) struct SvgCanvas<'w, W> {
+    out: &'w mut W,
+    symbols: &'w Symbols,
+    style: &'w SvgStyle,
+    rows: usize,
+    segments: Vec<(f64, f64, f64, f64)>,
+}
+impl<'w, W: Write> SvgCanvas<'w, W> {
+    pub(super) fn new(out: &'w mut W, symbols: &'w Symbols, style: &'w SvgStyle) -> Self {
+        SvgCanvas {
+            out,
+            symbols,
+            style,
+            rows: 0,
+            segments: Vec::new(),
+        }
+    }
+
+    /// Opens the document, sized to fit the given number of columns and rows.
+    pub(super) fn begin(&mut self, columns: usize, rows: usize) -> fmt::Result {
+        let style = self.style;
+        let width = px(columns as f64 * style.column_width + 2.0 * style.padding);
+        let height = px(rows as f64 * style.row_height + 2.0 * style.padding);
+        writeln!(
+            self.out,
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" \
+             width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">",
+            w = width,
+            h = height,
+        )?;
+        self.out.write_str("<style>")?;
+        write!(
+            self.out,
+            "path{{fill:none;stroke:{};stroke-width:{};stroke-linecap:square}}",
+            sanitize_css(&style.stroke),
+            px(style.stroke_width),
+        )?;
+        write!(
+            self.out,
+            "text{{font-family:{};font-size:{}px;fill:{};dominant-baseline:central;white-space:pre}}",
+            sanitize_css(&style.font_family),
+            px(style.font_size),
+            sanitize_css(&style.text_color),
+        )?;
+        self.out.write_str("</style>\n")
+    }
+
+    pub(super) fn end(&mut self) -> fmt::Result {
+        self.out.write_str("</svg>\n")
+    }
+
+    fn horizontal(&mut self, from: f64, to: f64, y: f64) {
+        self.segments.push((from, y, to, y));
+    }
+}
+impl<W: Write> Canvas for SvgCanvas<'_, W> {
+    fn row(&mut self, cells: &[Cell]) -> fmt::Result {
+        let style = self.style;
+        let (cw, rh) = (style.column_width, style.row_height);
+        let top = style.padding + self.rows as f64 * rh;
+        let middle = top + rh / 2.0;
+        self.rows += 1;
+
+        self.segments.clear();
+        let mut column = 0;
+        for cell in cells {
+            let left = style.padding + column as f64 * cw;
+            let width = cell.width(self.symbols);
+            match cell {
+                Cell::Space(_) | Cell::Label { .. } => (),
+                Cell::Line(n) => self.horizontal(left, left + *n as f64 * cw, middle),
+                Cell::Cap(_) if width == 0 => (),
+                Cell::Cap(Cap::Right) => {
+                    self.horizontal(left, left + (width as f64 - 0.5) * cw, middle)
+                }
+                Cell::Cap(Cap::Left) => {
+                    self.horizontal(left + 0.5 * cw, left + width as f64 * cw, middle)
+                }
+                Cell::Joint(joint) => {
+                    let center = left + cw / 2.0;
+                    if joint.up {
+                        self.segments.push((center, middle, center, top));
+                    }
+                    if joint.down {
+                        self.segments.push((center, middle, center, top + rh));
+                    }
+                    if joint.left {
+                        self.horizontal(center, left, middle);
+                    }
+                    if joint.right {
+                        self.horizontal(center, left + cw, middle);
+                    }
+                }
+            }
+            column += width;
+        }
+
+        if !self.segments.is_empty() {
+            self.out.write_str("<path d=\"")?;
+            for (index, (x1, y1, x2, y2)) in self.segments.iter().enumerate() {
+                if index > 0 {
+                    self.out.write_char(' ')?;
+                }
+                write!(self.out, "M{} {}L{} {}", px(*x1), px(*y1), px(*x2), px(*y2))?;
+            }
+            self.out.write_str("\"/>\n")?;
+        }
+
+        let mut column = 0;
+        for cell in cells {
+            let width = cell.width(self.symbols);
+            if let Cell::Label { text: label, .. } = cell {
+                write!(
+                    self.out,
+                    "<text x=\"{}\" y=\"{}\" textLength=\"{}\" lengthAdjust=\"spacingAndGlyphs\">",
+                    px(style.padding + column as f64 * cw),
+                    px(middle),
+                    px(text_width(label) as f64 * cw),
+                )?;
+                escape_xml(self.out, label)?;
+                self.out.write_str("</text>\n")?;
+            }
+            column += width;
+        }
+        Ok(())
+    }
+}
+
+/// Rounds to two decimals, so coordinates stay short.
+fn px(value: f64) -> f64 {
+    (value * 100.0).round() / 100.0
+}
+
+/// Keeps style values from ending the declaration or the style element.
+fn sanitize_css(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| !matches!(c, '<' | '>' | ';' | '{' | '}'))
+        .collect()
+}
@@ -0,0 +1,278 @@
+use std::{fmt, ops::Range};
+
+use super::{
+    canvas::{label_lines, label_width, text_width, Canvas, Cell, Joint},
+    Alignment, RenderMode,
+};
+use crate::Node;
+
+pub(super) fn layout<C: Canvas>(mode: &RenderMode, node: &Node, canvas: C) -> fmt::Result {
+    let mut vertical = Vertical::new(mode, node, canvas);
+    vertical.measure();
+    vertical.place();
+    vertical.draw()
+}
+
+/// Position of a node in the grid.
+///
+/// `label` and `anchor` are relative to `start` until placed.
+struct Slot<'a> {
+    node: &'a Node,
+    first_child: usize,
+    start: usize,
+    span: usize,
+    label: usize,
+    anchor: usize,
+}
+impl<'a> Slot<'a> {
+    fn new(node: &'a Node) -> Self {
+        Slot {
+            node,
+            first_child: 0,
+            start: 0,
+            span: 0,
+            label: 0,
+            anchor: 0,
+        }
+    }
+
+    fn children(&self) -> Range<usize> {
+        self.first_child..self.first_child + self.node.children.len()
+    }
+}
+
+struct Vertical<'a, C> {
+    mode: &'a RenderMode,
+    canvas: C,
+    levels: Vec<Vec<Slot<'a>>>,
+    row: Vec<Cell<'a>>,
+}
+impl<'a, C: Canvas> Vertical<'a, C> {
+    fn new(mode: &'a RenderMode, node: &'a Node, canvas: C) -> Self {
+        let mut levels = vec![vec![Slot::new(node)]];
+        loop {
+            let mut next = Vec::new();
+            for slot in levels.last_mut().unwrap() {
+                slot.first_child = next.len();
+                next.extend(slot.node.children.iter().map(Slot::new));
+            }
+            if next.is_empty() {
+                break;
+            }
+            levels.push(next);
+        }
+
+        Vertical {
+            mode,
+            canvas,
+            levels,
+            row: Vec::new(),
+        }
+    }
+
+    fn separation(&self) -> usize {
+        1 + self.mode.gaps as usize
+    }
+
+    /// Computes spans, label and anchor offsets from the bottom up.
+    fn measure(&mut self) {
+        let separation = self.separation();
+        for depth in (0..self.levels.len()).rev() {
+            let (upper, lower) = self.levels.split_at_mut(depth + 1);
+            let children = lower.first().map(Vec::as_slice).unwrap_or_default();
+            for slot in upper[depth].iter_mut() {
+                let width = label_width(&slot.node.name);
+                let children = &children[slot.children()];
+                let block = children.iter().map(|c| c.span).sum::<usize>()
+                    + separation * children.len().saturating_sub(1);
+                slot.span = width.max(block);
+
+                let latest = slot.span - width;
+                slot.label = match self.mode.align_root {
+                    Alignment::Start => 0,
+                    Alignment::End => latest,
+                    Alignment::Middle => match (children.first(), children.last()) {
+                        (Some(first), Some(last)) => {
+                            let offset = (slot.span - block) / 2;
+                            let first = offset + first.anchor;
+                            let last = offset + block - last.span + last.anchor;
+                            ((first + last) / 2)
+                                .saturating_sub(width.saturating_sub(1) / 2)
+                                .min(latest)
+                        }
+                        _ => latest / 2,
+                    },
+                };
+                slot.anchor = slot.label
+                    + match self.mode.align_root {
+                        Alignment::Start => 0,
+                        Alignment::End => width.saturating_sub(1),
+                        Alignment::Middle => width.saturating_sub(1) / 2,
+                    };
+            }
+        }
+    }
+
+    /// Turns relative offsets into columns from the top down.
+    fn place(&mut self) {
+        let separation = self.separation();
+        for depth in 0..self.levels.len() {
+            let (upper, lower) = self.levels.split_at_mut(depth + 1);
+            let slots = &mut upper[depth];
+            for slot in slots.iter_mut() {
+                slot.label += slot.start;
+                slot.anchor += slot.start;
+            }
+            let Some(children) = lower.first_mut() else {
+                continue;
+            };
+            for slot in slots.iter() {
+                let children = &mut children[slot.children()];
+                let block = children.iter().map(|c| c.span).sum::<usize>()
+                    + separation * children.len().saturating_sub(1);
+                let mut start = slot.start
+                    + match self.mode.align_root {
+                        Alignment::Start => 0,
+                        Alignment::End => slot.span - block,
+                        Alignment::Middle => (slot.span - block) / 2,
+                    };
+                for child in children {
+                    child.start = start;
+                    start += child.span + separation;
+                }
+            }
+        }
+    }
+
+    fn draw(&mut self) -> fmt::Result {
+        let last = self.levels.len() - 1;
+        match self.mode.invert {
+            false => {
+                for depth in 0..last {
+                    self.labels(depth)?;
+                    self.connectors(depth)?;
+                    self.lines(depth + 1)?;
+                }
+                self.labels(last)
+            }
+            true => {
+                self.labels(last)?;
+                for depth in (0..last).rev() {
+                    self.lines(depth + 1)?;
+                    self.connectors(depth)?;
+                    self.labels(depth)?;
+                }
+                Ok(())
+            }
+        }
+    }
+
+    /// Draws the labels at `depth` as blocks, top-aligned.
+    ///
+    /// Below shorter labels, a line continues towards the connectors underneath.
+    fn labels(&mut self, depth: usize) -> fmt::Result {
+        let height = self.levels[depth]
+            .iter()
+            .map(|slot| label_lines(&slot.node.name).count())
+            .max()
+            .unwrap_or(1);
+        for line in 0..height {
+            self.row.clear();
+            let mut column = 0;
+            for slot in &self.levels[depth] {
+                let linked = match self.mode.invert {
+                    true => depth > 0,
+                    false => !slot.node.children.is_empty(),
+                };
+                match label_lines(&slot.node.name).nth(line) {
+                    Some(text) => {
+                        self.row.push(Cell::Space(slot.label - column));
+                        self.row.push(Cell::Label {
+                            text,
+                            depth,
+                            leaf: slot.node.children.is_empty(),
+                        });
+                        column = slot.label + text_width(text);
+                    }
+                    None if linked => {
+                        self.row.push(Cell::Space(slot.anchor - column));
+                        self.row.push(Cell::Joint(Joint::VERTICAL));
+                        column = slot.anchor + 1;
+                    }
+                    None => (),
+                }
+            }
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn connectors(&mut self, depth: usize) -> fmt::Result {
+        self.row.clear();
+        let mut column = 0;
+        let (upper, lower) = self.levels.split_at(depth + 1);
+        for slot in &upper[depth] {
+            let children = &lower[0][slot.children()];
+            let (Some(first), Some(last)) = (children.first(), children.last()) else {
+                continue;
+            };
+            let low = slot.anchor.min(first.anchor);
+            let high = slot.anchor.max(last.anchor);
+
+            let mut anchors = children.iter().map(|c| c.anchor).peekable();
+            let mut parent = Some(slot.anchor);
+            self.row.push(Cell::Space(low - column));
+            column = low;
+            loop {
+                let point = match (parent, anchors.peek()) {
+                    (Some(p), Some(&a)) => p.min(a),
+                    (Some(p), None) => p,
+                    (None, Some(&a)) => a,
+                    (None, None) => break,
+                };
+                if point > column {
+                    self.row.push(Cell::Line(point - column));
+                }
+                let up = parent == Some(point);
+                let down = anchors.peek() == Some(&point);
+                if up {
+                    parent = None;
+                }
+                if down {
+                    anchors.next();
+                }
+                self.row.push(Cell::Joint(Joint {
+                    up,
+                    down,
+                    left: point > low,
+                    right: point < high,
+                }));
+                column = point + 1;
+            }
+        }
+        self.flush()
+    }
+
+    fn lines(&mut self, depth: usize) -> fmt::Result {
+        for _ in 0..self.mode.lines {
+            self.row.clear();
+            let mut column = 0;
+            for slot in &self.levels[depth] {
+                self.row.push(Cell::Space(slot.anchor - column));
+                self.row.push(Cell::Joint(Joint::VERTICAL));
+                column = slot.anchor + 1;
+            }
+            self.flush()?;
+        }
+        Ok(())
+    }
+
+    fn flush(&mut self) -> fmt::Result {
+        self.row
+            .retain(|c| !matches!(c, Cell::Space(0) | Cell::Line(0)));
+        if self.mode.invert {
+            self.row.iter_mut().for_each(|c| *c = c.flipped());
+        }
+        self.canvas.row(&self.row)
+    }
+}
@@ -0,0 +1,67 @@
+use std::collections::HashSet;
+
+use crate::Node;
+
+/// Segment matching any number of levels, including none.
+const ANY_DEPTH: &str = "**";
+
+/// Collects the descendants of `node` reached by following `segments`.
+///
+/// `node` itself is left out, even where `**` matches no levels.
+pub(crate) fn select<'a>(node: &'a Node, segments: &[&str], found: &mut Vec<&'a Node>) {
+    let mut seen = HashSet::from([node as *const Node]);
+    walk(node, segments, &mut |node| {
+        if seen.insert(node as *const Node) {
+            found.push(node);
+        }
+    });
+}
+
+fn walk<'a>(node: &'a Node, segments: &[&str], found: &mut impl FnMut(&'a Node)) {
+    match segments.split_first() {
+        None => found(node),
+        Some((&ANY_DEPTH, rest)) => {
+            walk(node, rest, found);
+            for child in &node.children {
+                walk(child, segments, found);
+            }
+        }
+        Some((segment, rest)) => {
+            for child in &node.children {
+                if glob(segment, &child.name) {
+                    walk(child, rest, found);
+                }
+            }
+        }
+    }
+}
+
+/// Matches `name` against `pattern`, where `*` stands for any number of
+/// characters and `?` for exactly one.
+fn glob(pattern: &str, name: &str) -> bool {
+    let pattern = pattern.chars().collect::<Vec<_>>();
+    let name = name.chars().collect::<Vec<_>>();
+    let (mut p, mut n) = (0, 0);
+    let mut retry = None;
+    while n < name.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                retry = Some((p, n));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == name[n] => {
+                p += 1;
+                n += 1;
+            }
+            _ => match retry {
+                Some((star, at)) => {
+                    retry = Some((star, at + 1));
+                    p = star + 1;
+                    n = at + 1;
+                }
+                None => return false,
+            },
+        }
+    }
+    pattern[p..].iter().all(|&c| c == '*')
+}
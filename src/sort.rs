@@ -0,0 +1,203 @@
+use std::{cmp::Ordering, iter::Peekable, str::Chars};
+
+/// Size of a subtree, measured bottom-up while sorting.
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct Shape {
+    pub(crate) children: usize,
+    pub(crate) descendants: usize,
+    pub(crate) height: usize,
+}
+impl Shape {
+    /// Shape of a node with children of the given shapes.
+    pub(crate) fn above(children: &[Shape]) -> Self {
+        Shape {
+            children: children.len(),
+            descendants: children.iter().map(|c| c.descendants + 1).sum(),
+            height: children.iter().map(|c| c.height + 1).max().unwrap_or(0),
+        }
+    }
+}
+
+/// Compares runs of digits by their value and everything else by character.
+pub(crate) fn natural(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.chars().peekable(), b.chars().peekable());
+    loop {
+        let (x, y) = match (a.peek(), b.peek()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(&x), Some(&y)) => (x, y),
+        };
+        let order = match x.is_ascii_digit() && y.is_ascii_digit() {
+            true => digits(&mut a).cmp_numeric(&digits(&mut b)),
+            false => {
+                a.next();
+                b.next();
+                x.cmp(&y)
+            }
+        };
+        if order.is_ne() {
+            return order;
+        }
+    }
+}
+
+/// Compares the numbers names start with. Names without one go last.
+pub(crate) fn numeric(a: &str, b: &str) -> Ordering {
+    match (leading_number(a), leading_number(b)) {
+        (Some(x), Some(y)) => x.total_cmp(&y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    }
+}
+
+pub(crate) fn case_insensitive(a: &str, b: &str) -> Ordering {
+    a.chars()
+        .flat_map(char::to_lowercase)
+        .cmp(b.chars().flat_map(char::to_lowercase))
+}
+
+pub(crate) fn length(a: &str, b: &str) -> Ordering {
+    a.chars().count().cmp(&b.chars().count())
+}
+
+/// Compares version numbers like `v1.10.0-rc.2`.
+///
+/// Dot-separated parts compare naturally, a missing part counts as lower.
+/// A pre-release after `-` sorts before the same version without one,
+/// build metadata after `+` is ignored.
+pub(crate) fn version(a: &str, b: &str) -> Ordering {
+    let ((a, a_pre), (b, b_pre)) = (split_version(a), split_version(b));
+    parts(a, b).then_with(|| match (a_pre, b_pre) {
+        (Some(x), Some(y)) => parts(x, y),
+        (Some(_), None) => Ordering::Less,
+        (None, Some(_)) => Ordering::Greater,
+        (None, None) => Ordering::Equal,
+    })
+}
+
+/// Splits a version into the release and pre-release.
+fn split_version(name: &str) -> (&str, Option<&str>) {
+    let name = name.strip_prefix(['v', 'V']).unwrap_or(name);
+    let name = name.split_once('+').map_or(name, |(core, _)| core);
+    match name.split_once('-') {
+        Some((core, pre)) => (core, Some(pre)),
+        None => (name, None),
+    }
+}
+
+fn parts(a: &str, b: &str) -> Ordering {
+    let (mut a, mut b) = (a.split('.'), b.split('.'));
+    loop {
+        match (a.next(), b.next()) {
+            (None, None) => return Ordering::Equal,
+            (None, Some(_)) => return Ordering::Less,
+            (Some(_), None) => return Ordering::Greater,
+            (Some(x), Some(y)) => match natural(x, y) {
+                Ordering::Equal => continue,
+                order => return order,
+            },
+        }
+    }
+}
+
+/// Digits taken from the front of a name, without leading zeros.
+struct Digits(String);
+impl Digits {
+    fn cmp_numeric(&self, other: &Digits) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.cmp(&other.0))
+    }
+}
+
+fn digits(chars: &mut Peekable<Chars>) -> Digits {
+    let mut digits = String::new();
+    while let Some(c) = chars.next_if(char::is_ascii_digit) {
+        if !(digits.is_empty() && c == '0') {
+            digits.push(c);
+        }
+    }
+    Digits(digits)
+}
+
+fn leading_number(name: &str) -> Option<f64> {
+    let name = name.trim_start();
+    let end = name
+        .char_indices()
+        .find(|&(pos, c)| !(c.is_ascii_digit() || c == '.' || (pos == 0 && matches!(c, '-' | '+'))))
+        .map_or(name.len(), |(pos, _)| pos);
+    (1..=end)
+        .rev()
+        .find_map(|end| name[..end].parse::<f64>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn natural_compares_numbers_by_value() {
+        assert_eq!(natural("item2", "item10"), Ordering::Less);
+        assert_eq!(natural("item10", "item9"), Ordering::Greater);
+        assert_eq!(natural("a1b2", "a1b10"), Ordering::Less);
+        assert_eq!(natural("item", "item1"), Ordering::Less);
+    }
+
+    #[test]
+    fn natural_ignores_leading_zeros() {
+        assert_eq!(natural("item007", "item7"), Ordering::Equal);
+        assert_eq!(natural("item010", "item9"), Ordering::Greater);
+        assert_eq!(natural("0", "00"), Ordering::Equal);
+    }
+
+    #[test]
+    fn natural_compares_other_characters_by_code_point() {
+        assert_eq!(natural("B", "a"), Ordering::Less);
+        assert_eq!(natural("a1", "a-"), Ordering::Greater);
+    }
+
+    #[test]
+    fn numeric_compares_leading_numbers() {
+        assert_eq!(numeric("9 apples", "10 apples"), Ordering::Less);
+        assert_eq!(numeric("-3", "2"), Ordering::Less);
+        assert_eq!(numeric("1.5kg", "1.25kg"), Ordering::Greater);
+        assert_eq!(numeric("007", "7"), Ordering::Equal);
+        assert_eq!(numeric("  4", "5"), Ordering::Less);
+    }
+
+    #[test]
+    fn numeric_puts_names_without_numbers_last() {
+        assert_eq!(numeric("apples", "10"), Ordering::Greater);
+        assert_eq!(numeric("1.", "apples"), Ordering::Less);
+        assert_eq!(numeric("apples", "pears"), Ordering::Equal);
+        assert_eq!(numeric("-", "+"), Ordering::Equal);
+    }
+
+    #[test]
+    fn version_compares_parts_naturally() {
+        assert_eq!(version("1.9", "1.10"), Ordering::Less);
+        assert_eq!(version("1.10", "2.0"), Ordering::Less);
+        assert_eq!(version("1.2", "1.2.0"), Ordering::Less);
+        assert_eq!(version("1.02", "1.2"), Ordering::Equal);
+    }
+
+    #[test]
+    fn version_ignores_prefix_and_build_metadata() {
+        assert_eq!(version("v1.2.3", "1.2.3"), Ordering::Equal);
+        assert_eq!(version("V2", "v10"), Ordering::Less);
+        assert_eq!(version("1.2.3+build.9", "1.2.3+build.10"), Ordering::Equal);
+        assert_eq!(version("1.2.3+20240101", "1.2.4"), Ordering::Less);
+    }
+
+    #[test]
+    fn version_puts_pre_releases_first() {
+        assert_eq!(version("2.0-rc1", "2.0"), Ordering::Less);
+        assert_eq!(version("2.0-rc.2", "2.0-rc.10"), Ordering::Less);
+        assert_eq!(version("2.0-alpha", "2.0-beta"), Ordering::Less);
+        assert_eq!(version("2.0-rc1+build", "2.0"), Ordering::Less);
+        assert_eq!(version("1.9", "2.0-rc1"), Ordering::Less);
+    }
+}